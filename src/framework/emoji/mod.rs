@@ -4,3 +4,7 @@ pub mod flags;
 pub const SUCCESS: &str = "✅";
 pub const FAILED: &str = "❌";
 pub const STOPWATCH: &str = "⏱️";
+pub const PREVIOUS: &str = "◀️";
+pub const NEXT: &str = "▶️";
+pub const SKIP: &str = "⏭️";
+pub const NUMBERS: [&str; 10] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "🔟"];
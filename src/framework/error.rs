@@ -1,3 +1,4 @@
+use serenity::model::id::ChannelId;
 use thiserror::Error as ThisError;
 
 #[derive(ThisError, Debug)]
@@ -13,8 +14,48 @@ pub enum Error {
     AlreadyMuted,
     #[error("Not in voice channel")]
     NotInVoiceChannel,
-    #[error("Not seekable")]
+    #[error("Nothing is playing")]
+    NothingPlaying,
+    #[error("Already paused")]
+    AlreadyPaused,
+    #[error("Not paused")]
+    NotPaused,
+    #[error("No queued track at position {0}")]
+    QueuePositionOutOfRange(usize),
+    #[error("No history entry at position {0}")]
+    HistoryPositionOutOfRange(usize),
+    #[error("This track can't be seeked, live streams have no timeline")]
     NotSeekable,
+    #[error("No search results")]
+    NoSearchResults,
+    #[error("No track was selected in time")]
+    SelectionTimedOut,
+    #[error("No music library is configured")]
+    LibraryNotConfigured,
+    #[error("The music library is still being indexed")]
+    LibraryIndexing,
+    #[error("There is no playlist named `{0}`")]
+    PlaylistNotFound(String),
+    #[error("There already is a playlist named `{0}`")]
+    PlaylistExists(String),
+    #[error("Playlist `{0}` has no track at position {1}")]
+    PlaylistPositionOutOfRange(String, usize),
+    #[error("Only members with the playlist role can change guild playlists")]
+    PlaylistNotEditable,
+    #[error("Only DJs can do that")]
+    NotDj,
+    #[error("You need to be in <#{0}> to do that")]
+    NotInSameVoiceChannel(ChannelId),
+    #[error("The queue is full, it holds at most {0} tracks")]
+    QueueFull(usize),
+    #[error("You already have {0} tracks queued, the most a member may")]
+    UserTrackLimit(usize),
+    #[error("Tracks may be at most {0} long")]
+    TrackTooLong(String),
+    #[error("That track is already queued")]
+    DuplicateTrack,
+    #[error("Wait {0} more seconds before queueing again")]
+    EnqueueCooldown(u64),
     #[error("Songbird Voice client placed in at initialization")]
     SongbirdInitialization,
 
@@ -1,6 +1,6 @@
 use serenity::{
     client::Context,
-    framework::standard::{CommandResult, macros::hook},
+    framework::standard::{CommandResult, DispatchError, Reason, macros::hook},
     model::channel::Message,
 };
 
@@ -79,3 +79,14 @@ pub async fn after(
         }
     };
 }
+
+#[hook]
+pub async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError, command_name: &str) {
+    println!("Command '{}' was not dispatched: {:?}", command_name, error);
+
+    if let DispatchError::CheckFailed(_, Reason::User(reason) | Reason::UserAndLog { user: reason, .. }) = error {
+        msg.reply_ping(&ctx.http, format!("Command Failed. The problem was:\n```{}```", reason))
+            .await
+            .ok();
+    }
+}
@@ -0,0 +1,50 @@
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandOptions, Reason, macros::check},
+    model::channel::Message,
+};
+
+use crate::framework::error::Error;
+
+use super::idle;
+use super::vote;
+
+// Passes DJs only, by the same rule as `vote::require_dj` so `help` shows what actually runs.
+#[check]
+#[name = "DJ"]
+async fn dj(ctx: &Context, msg: &Message, _args: &mut Args, _options: &CommandOptions) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    vote::require_dj(ctx, guild_id, msg.author.id)
+        .await
+        .map_err(|e| Reason::User(e.to_string()))
+}
+
+// Passes members listening in the bot's voice channel, or everyone while the bot isn't in one.
+//
+// Not evaluated by `help`, which would otherwise hide most music commands from anyone
+// who just isn't in a voice channel at the moment.
+#[check]
+#[name = "SameVoiceChannel"]
+#[check_in_help(false)]
+async fn same_voice_channel(
+    ctx: &Context,
+    msg: &Message,
+    _args: &mut Args,
+    _options: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    match idle::listeners(ctx, guild_id) {
+        Some((channel_id, users)) if !users.contains(&msg.author.id) => {
+            Err(Reason::User(Error::NotInSameVoiceChannel(channel_id).to_string()))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use serenity::{
+    async_trait,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{channel::Message, id::GuildId},
+};
+use songbird::{
+    tracks::{LoopState, PlayMode, TrackHandle},
+    Event, EventContext, EventHandler as VoiceEventHandler,
+};
+
+use crate::framework::error::Error;
+
+use super::settings;
+use super::vote;
+
+/// How often a volume ramp moves the volume along.
+const RAMP_STEP: Duration = Duration::from_millis(50);
+/// Longest time between two looks at how much of a track is left, so seeks can't make us miss its end.
+const MAX_SCHEDULE: Duration = Duration::from_secs(10);
+const MAX_FADE_SECONDS: f64 = 15.0;
+
+/// Moves a track's volume from `from` to `to`, optionally stopping the track once there.
+struct VolumeRamp {
+    from: f32,
+    to: f32,
+    steps: usize,
+    step: AtomicUsize,
+    stop: bool,
+}
+
+#[async_trait]
+impl VoiceEventHandler for VolumeRamp {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let &(_, track) = match ctx {
+            EventContext::Track(track_list) => track_list.first()?,
+            _ => return None,
+        };
+
+        let step = self.step.fetch_add(1, Ordering::Relaxed) + 1;
+        let progress = (step as f32 / self.steps as f32).min(1.0);
+        track.set_volume(self.from + (self.to - self.from) * progress).ok();
+
+        if step < self.steps {
+            return None;
+        }
+        if self.stop {
+            track.stop().ok();
+        }
+
+        Some(Event::Cancel)
+    }
+}
+
+/// Ramps the track's volume from `from` to `to` over `duration` of playback.
+pub fn ramp(track: &TrackHandle, from: f32, to: f32, duration: Duration, stop: bool) -> Result<(), Error> {
+    let steps = (duration.as_millis() / RAMP_STEP.as_millis()).max(1) as usize;
+
+    track
+        .add_event(
+            Event::Periodic(RAMP_STEP, None),
+            VolumeRamp {
+                from,
+                to,
+                steps,
+                step: AtomicUsize::new(0),
+                stop,
+            },
+        )
+        .map_err(|e| Error::Other(e.into()))
+}
+
+/// Stops a track after fading it out, or right away if it isn't audible anyway.
+pub async fn stop(track: &TrackHandle, fade_out: Duration) -> Result<(), Error> {
+    let state = track.get_info().await.map_err(|e| Error::Other(e.into()))?;
+
+    // Ramps only advance while the track plays, a paused track would never stop.
+    if fade_out.is_zero() || state.playing != PlayMode::Play {
+        track.stop().map_err(|e| Error::Other(e.into()))
+    } else {
+        ramp(track, state.volume, 0.0, fade_out, true)
+    }
+}
+
+/// Fades a track out shortly before its end and, with crossfade enabled, starts the
+/// next queued track while this one is still fading.
+pub struct Outro {
+    pub ctx: Context,
+    pub guild_id: GuildId,
+    pub fade_out: Duration,
+    pub crossfade: Duration,
+    pub faded: AtomicBool,
+}
+
+impl Outro {
+    fn window(&self) -> Duration {
+        self.fade_out.max(self.crossfade)
+    }
+
+    /// The delay of the first check, for a track lasting `duration`.
+    pub fn schedule(&self, duration: Duration) -> Event {
+        Event::Delayed(duration.saturating_sub(self.window()).min(MAX_SCHEDULE))
+    }
+}
+
+#[async_trait]
+impl VoiceEventHandler for Outro {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let &(state, track) = match ctx {
+            EventContext::Track(track_list) => track_list.first()?,
+            _ => return None,
+        };
+
+        // A looping track starts over instead of ending, keep watching in case looping is turned off.
+        if state.loops != LoopState::Finite(0) {
+            return Some(Event::Delayed(MAX_SCHEDULE));
+        }
+
+        let remaining = track.metadata().duration?.saturating_sub(state.position);
+        if remaining > self.window() {
+            return Some(self.schedule(remaining));
+        }
+
+        if !self.faded.swap(true, Ordering::Relaxed) {
+            ramp(track, state.volume, 0.0, remaining, false).ok();
+        }
+
+        if self.crossfade.is_zero() {
+            return None;
+        }
+        if remaining > self.crossfade {
+            return Some(Event::Delayed(remaining - self.crossfade));
+        }
+
+        // The builtin queue only moves on once this track ends, by then the next one is already playing.
+        let handler_lock = songbird::get(&self.ctx).await?.get(self.guild_id)?;
+        let next = handler_lock.lock().await.queue().current_queue().get(1).cloned();
+        if let Some(next) = next {
+            next.play().ok();
+        }
+
+        None
+    }
+}
+
+fn format_seconds(duration: Duration) -> String {
+    if duration.is_zero() {
+        "off".to_string()
+    } else {
+        format!("{}s", duration.as_secs_f64())
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(2)]
+#[aliases("crossfade")]
+async fn fade(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let usage = || {
+        Error::DetailedInvalidArguments(format!(
+            "in, out or crossfade followed by 0 to {} seconds",
+            MAX_FADE_SECONDS
+        ))
+    };
+
+    if !args.is_empty() {
+        vote::require_dj(ctx, guild_id, msg.author.id).await?;
+
+        let kind = args.single::<String>().map_err(|_| usage())?.to_lowercase();
+        let millis = args
+            .single::<f64>()
+            .ok()
+            .filter(|seconds| (0.0..=MAX_FADE_SECONDS).contains(seconds))
+            .map(|seconds| (seconds * 1000.0).round() as u64)
+            .ok_or_else(usage)?;
+
+        match kind.as_str() {
+            "in" => settings::update(ctx, guild_id, |s| s.fade_in = millis).await?,
+            "out" => settings::update(ctx, guild_id, |s| s.fade_out = millis).await?,
+            "cross" | "crossfade" | "xfade" => {
+                settings::update(ctx, guild_id, |s| s.crossfade = millis).await?
+            }
+            _ => Err(usage())?,
+        };
+    }
+
+    let guild_settings = settings::get(ctx, guild_id).await;
+    msg.reply(
+        &ctx.http,
+        format!(
+            "Fade in {} · fade out {} · crossfade {}",
+            format_seconds(guild_settings.fade_in()),
+            format_seconds(guild_settings.fade_out()),
+            format_seconds(guild_settings.crossfade()),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::Message,
+};
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::player;
+use super::settings;
+use super::vote;
+
+const FILTER_USAGE: &str =
+    "One of bass [dB], nightcore, speed <0.5-2>, pitch <0.5-2>, eq <Hz> <dB>, loudnorm or clear";
+
+/// An ffmpeg audio filter applied to every track of a guild.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AudioFilter {
+    Bass { gain: f64 },
+    Nightcore,
+    Speed { rate: f64 },
+    Pitch { factor: f64 },
+    Equalizer { frequency: u32, gain: f64 },
+    Loudnorm,
+}
+
+impl AudioFilter {
+    fn parse(name: &str, args: &mut Args) -> Result<Self, Error> {
+        let usage = || Error::DetailedInvalidArguments(FILTER_USAGE.into());
+        // Both atempo and our pitch shift stop making sense outside of an octave.
+        let factor = |args: &mut Args| {
+            args.single::<f64>()
+                .ok()
+                .filter(|factor| (0.5..=2.0).contains(factor))
+                .ok_or_else(usage)
+        };
+
+        match name {
+            "bass" | "bassboost" => Ok(AudioFilter::Bass {
+                gain: if args.is_empty() { 10.0 } else { args.single::<f64>().map_err(|_| usage())? },
+            }),
+            "nightcore" => Ok(AudioFilter::Nightcore),
+            "speed" | "tempo" => Ok(AudioFilter::Speed { rate: factor(args)? }),
+            "pitch" => Ok(AudioFilter::Pitch { factor: factor(args)? }),
+            "eq" | "equalizer" => Ok(AudioFilter::Equalizer {
+                frequency: args.single::<u32>().map_err(|_| usage())?,
+                gain: args.single::<f64>().map_err(|_| usage())?,
+            }),
+            "loudnorm" | "normalize" => Ok(AudioFilter::Loudnorm),
+            _ => Err(usage()),
+        }
+    }
+
+    /// Whether `other` would configure the same filter, so that it replaces this one.
+    fn replaces(&self, other: &AudioFilter) -> bool {
+        match (self, other) {
+            (
+                AudioFilter::Equalizer { frequency, .. },
+                AudioFilter::Equalizer { frequency: other, .. },
+            ) => frequency == other,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    /// How much faster than the source the filter plays it; pitch shifts keep the tempo.
+    fn tempo(&self) -> f64 {
+        match self {
+            AudioFilter::Nightcore => 60000.0 / 48000.0,
+            AudioFilter::Speed { rate } => *rate,
+            _ => 1.0,
+        }
+    }
+
+    fn to_ffmpeg(&self) -> String {
+        match self {
+            AudioFilter::Bass { gain } => format!("bass=g={}", gain),
+            AudioFilter::Nightcore => "aresample=48000,asetrate=60000,aresample=48000".to_string(),
+            AudioFilter::Speed { rate } => format!("atempo={}", rate),
+            AudioFilter::Pitch { factor } => format!(
+                "aresample=48000,asetrate={},aresample=48000,atempo={}",
+                (48000.0 * factor).round(),
+                1.0 / factor
+            ),
+            AudioFilter::Equalizer { frequency, gain } => {
+                format!("equalizer=f={}:t=o:w=1:g={}", frequency, gain)
+            }
+            AudioFilter::Loudnorm => "loudnorm".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AudioFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioFilter::Bass { gain } => write!(f, "bass {:+} dB", gain),
+            AudioFilter::Nightcore => write!(f, "nightcore"),
+            AudioFilter::Speed { rate } => write!(f, "speed {}x", rate),
+            AudioFilter::Pitch { factor } => write!(f, "pitch {}x", factor),
+            AudioFilter::Equalizer { frequency, gain } => {
+                write!(f, "eq {} Hz {:+} dB", frequency, gain)
+            }
+            AudioFilter::Loudnorm => write!(f, "loudnorm"),
+        }
+    }
+}
+
+/// Joins `filters` into a chain for ffmpeg's `-af`, or `None` when there is nothing to apply.
+pub fn chain(filters: &[AudioFilter]) -> Option<String> {
+    (!filters.is_empty()).then(|| {
+        filters
+            .iter()
+            .map(AudioFilter::to_ffmpeg)
+            .collect::<Vec<_>>()
+            .join(",")
+    })
+}
+
+/// How much faster than the source the whole chain plays it.
+pub fn tempo(filters: &[AudioFilter]) -> f64 {
+    filters.iter().map(AudioFilter::tempo).product()
+}
+
+fn describe(filters: &[AudioFilter]) -> String {
+    if filters.is_empty() {
+        return "No audio filters".to_string();
+    }
+
+    format!(
+        "Audio filters: {}",
+        filters
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[aliases("filters", "fx")]
+async fn filter(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if args.is_empty() {
+        let filters = settings::get(ctx, guild_id).await.filters;
+        msg.reply(&ctx.http, describe(&filters)).await?;
+
+        return Ok(());
+    }
+
+    vote::require_dj(ctx, guild_id, msg.author.id).await?;
+    let name = args
+        .single::<String>()
+        .map_err(|_| Error::InvalidArguments)?
+        .to_lowercase();
+
+    let guild_settings = match name.as_str() {
+        "clear" | "off" | "reset" => settings::update(ctx, guild_id, |s| s.filters.clear()).await?,
+        _ => {
+            let filter = AudioFilter::parse(&name, &mut args)?;
+            settings::update(ctx, guild_id, |s| {
+                s.filters.retain(|existing| !existing.replaces(&filter));
+                s.filters.push(filter);
+            })
+            .await?
+        }
+    };
+
+    // Filters are baked into the ffmpeg process, so the playing track has to be started anew.
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+    if let Some(handler_lock) = manager.get(guild_id) {
+        player::restart_current(ctx, guild_id, &handler_lock).await?;
+    }
+
+    msg.reply(&ctx.http, describe(&guild_settings.filters))
+        .await?;
+
+    Ok(())
+}
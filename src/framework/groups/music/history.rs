@@ -0,0 +1,215 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use serenity::{
+    async_trait,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{channel::Message, id::GuildId, Timestamp},
+};
+use songbird::{
+    tracks::{PlayMode, TrackHandle},
+    typemap::TypeMapKey,
+    Event, EventContext, EventHandler as VoiceEventHandler,
+};
+use tokio::sync::RwLock;
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::limits;
+use super::request::{self, Request};
+use super::source::{self, Source};
+use super::timestamp::format_duration;
+use super::{connect, player};
+
+const MAX_HISTORY: usize = 50;
+const SHOWN_ENTRIES: usize = 10;
+
+/// A track that was played in a guild, newest first in the guild's history.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    source: Source,
+    title: Option<String>,
+    request: Request,
+    /// How much of the track was heard before it ended.
+    played: Duration,
+    ended_at: Timestamp,
+    skipped: bool,
+}
+
+impl HistoryEntry {
+    fn title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+pub struct MusicHistory;
+
+impl TypeMapKey for MusicHistory {
+    type Value = Arc<RwLock<HashMap<u64, VecDeque<HistoryEntry>>>>;
+}
+
+/// Marks a track which is about to be stopped only to be replaced by a copy of itself.
+struct Superseded;
+
+impl TypeMapKey for Superseded {
+    type Value = ();
+}
+
+pub async fn supersede(track: &TrackHandle) {
+    track.typemap().write().await.insert::<Superseded>(());
+}
+
+async fn history_lock(ctx: &Context) -> Result<Arc<RwLock<HashMap<u64, VecDeque<HistoryEntry>>>>, Error> {
+    Ok(ctx
+        .data
+        .read()
+        .await
+        .get::<MusicHistory>()
+        .ok_or(Error::Unknown)?
+        .clone())
+}
+
+/// Writes every track that ends into the guild's history.
+pub struct HistoryRecorder {
+    pub ctx: Context,
+    pub guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for HistoryRecorder {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let &(state, track) = match ctx {
+            EventContext::Track(track_list) => track_list.first()?,
+            _ => return None,
+        };
+
+        // Tracks cleared from the queue before they ever played weren't heard at all.
+        if state.play_time.is_zero() || track.typemap().read().await.contains_key::<Superseded>() {
+            return None;
+        }
+
+        let entry = HistoryEntry {
+            source: source::of(track).await?,
+            title: track.metadata().title.clone(),
+            request: request::of(track).await?,
+            played: state.play_time,
+            ended_at: Timestamp::now(),
+            skipped: state.playing != PlayMode::End,
+        };
+
+        let history_lock = history_lock(&self.ctx).await.ok()?;
+        let mut history = history_lock.write().await;
+        let entries = history.entry(self.guild_id.0).or_default();
+        entries.push_front(entry);
+        entries.truncate(MAX_HISTORY);
+
+        None
+    }
+}
+
+/// Returns the `number`th most recent entry of the guild's history, counting from 1.
+async fn entry(ctx: &Context, guild_id: GuildId, number: usize) -> Result<HistoryEntry, Error> {
+    let history_lock = history_lock(ctx).await?;
+    let history = history_lock.read().await;
+
+    number
+        .checked_sub(1)
+        .and_then(|index| history.get(&guild_id.0)?.get(index))
+        .cloned()
+        .ok_or(Error::HistoryPositionOutOfRange(number))
+}
+
+async fn replay_entry(ctx: &Context, msg: &Message, number: usize) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let entry = entry(ctx, guild_id, number).await?;
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+
+    player::enqueue_next(ctx, guild_id, &handler_lock, entry.source.clone(), Request::from_message(msg))
+        .await?;
+    limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+
+    msg.reply(&ctx.http, format!("Up next: {}", entry.title()))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("hist", "recent")]
+async fn history(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let history_lock = history_lock(ctx).await?;
+    let entries = history_lock
+        .read()
+        .await
+        .get(&guild_id.0)
+        .cloned()
+        .unwrap_or_default();
+
+    let description = if entries.is_empty() {
+        "Nothing has been played yet".to_string()
+    } else {
+        entries
+            .iter()
+            .take(SHOWN_ENTRIES)
+            .enumerate()
+            .map(|(index, entry)| {
+                let title = match &entry.source {
+                    Source::Ytdl { url } | Source::Stream { url, .. } => {
+                        format!("[{}]({})", entry.title(), url)
+                    }
+                    Source::File { .. } => entry.title(),
+                };
+                format!(
+                    "`{}.` {} `{}` · <@{}> · <t:{}:R>{}",
+                    index + 1,
+                    title,
+                    format_duration(entry.played),
+                    entry.request.user_id,
+                    entry.ended_at.unix_timestamp(),
+                    if entry.skipped { " · skipped" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Recently played")
+                    .description(description)
+                    .footer(|f| f.text("Replay one with `replay <number>`"))
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[aliases("prev", "back")]
+async fn previous(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    replay_entry(ctx, msg, 1).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[num_args(1)]
+async fn replay(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let number = args.single::<usize>().map_err(|_| {
+        Error::DetailedInvalidArguments("The number of the track in `history`".into())
+    })?;
+
+    replay_entry(ctx, msg, number).await
+}
@@ -0,0 +1,144 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use serenity::{
+    async_trait,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId, UserId},
+    },
+};
+use songbird::{Event, EventContext, EventHandler as VoiceEventHandler};
+
+use crate::framework::error::Error;
+
+use super::session;
+use super::settings;
+use super::snapshot;
+use super::vote;
+
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Returns the voice channel the bot is in and the non-bot users listening there.
+pub fn listeners(ctx: &Context, guild_id: GuildId) -> Option<(ChannelId, Vec<UserId>)> {
+    let guild = ctx.cache.guild(guild_id)?;
+    let bot_id = ctx.cache.current_user_id();
+    let channel_id = guild.voice_states.get(&bot_id)?.channel_id?;
+
+    let users = guild
+        .voice_states
+        .values()
+        .filter(|state| state.channel_id == Some(channel_id))
+        .filter(|state| {
+            let is_bot = match ctx.cache.user(state.user_id) {
+                Some(user) => user.bot,
+                None => state.member.as_ref().is_some_and(|m| m.user.bot),
+            };
+            !is_bot
+        })
+        .map(|state| state.user_id)
+        .collect();
+
+    Some((channel_id, users))
+}
+
+/// Leaves the guild's voice channel and tells the text channel the bot was summoned from why.
+pub async fn disconnect(ctx: &Context, guild_id: GuildId, reason: &str) -> Result<(), Error> {
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    manager
+        .remove(guild_id)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+    snapshot::forget(ctx, guild_id).await?;
+
+    if let Some(channel_id) = session::get(ctx, guild_id).await.text_channel_id {
+        channel_id.say(&ctx.http, reason).await.ok();
+    }
+
+    Ok(())
+}
+
+/// Called on every voice state change; leaves once nobody but bots is left listening.
+pub async fn leave_if_alone(ctx: &Context, guild_id: GuildId) {
+    if let Some((_, users)) = listeners(ctx, guild_id) {
+        if users.is_empty() {
+            disconnect(ctx, guild_id, "Everyone left the voice channel, so I left too.")
+                .await
+                .ok();
+        }
+    }
+}
+
+/// Leaves the voice channel once the queue has been empty for the guild's idle timeout.
+pub struct IdleNotifier {
+    pub ctx: Context,
+    pub guild_id: GuildId,
+    pub idle_checks: AtomicUsize,
+}
+
+#[async_trait]
+impl VoiceEventHandler for IdleNotifier {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+        let handler_lock = songbird::get(&self.ctx).await?.get(self.guild_id)?;
+
+        if !handler_lock.lock().await.queue().is_empty() {
+            self.idle_checks.store(0, Ordering::Relaxed);
+            return None;
+        }
+
+        let idle_timeout = settings::get(&self.ctx, self.guild_id).await.idle_timeout;
+        let idle_checks = self.idle_checks.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let idle_time = CHECK_INTERVAL * idle_checks as u32;
+        if idle_timeout == 0 || idle_time < Duration::from_secs(idle_timeout) {
+            return None;
+        }
+
+        // Removing the call tears down the driver running this very handler.
+        let (ctx, guild_id) = (self.ctx.clone(), self.guild_id);
+        tokio::spawn(async move {
+            let reason = format!(
+                "Left the voice channel after {} minutes without music.",
+                idle_timeout / 60
+            );
+            disconnect(&ctx, guild_id, &reason).await.ok();
+        });
+
+        Some(Event::Cancel)
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+async fn autoleave(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if args.is_empty() {
+        let idle_timeout = settings::get(ctx, guild_id).await.idle_timeout;
+        let reply = match idle_timeout {
+            0 => "Auto-leave is disabled".to_string(),
+            _ => format!("Leaving after {} idle minutes", idle_timeout / 60),
+        };
+        msg.reply(&ctx.http, reply).await?;
+
+        return Ok(());
+    }
+
+    vote::require_dj(ctx, guild_id, msg.author.id).await?;
+    let minutes = args.single::<u64>().map_err(|_| {
+        Error::DetailedInvalidArguments("Idle minutes before leaving, 0 to disable".into())
+    })?;
+
+    settings::update(ctx, guild_id, |s| s.idle_timeout = minutes * 60).await?;
+
+    Ok(())
+}
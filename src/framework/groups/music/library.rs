@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde_json::Value;
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::Message,
+};
+use songbird::typemap::TypeMapKey;
+use tokio::{process::Command, sync::RwLock};
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::limits;
+use super::request::Request;
+use super::source::{self, Source};
+use super::timestamp::format_duration;
+use super::{connect, player};
+
+/// A file of the local music library with the tags ffprobe could read from it.
+#[derive(Clone, Debug)]
+pub struct LibraryTrack {
+    pub path: PathBuf,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl LibraryTrack {
+    pub fn display_name(&self) -> String {
+        let title = match &self.title {
+            Some(title) => title.clone(),
+            None => self
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+
+        match &self.artist {
+            Some(artist) => format!("{} - {}", artist, title),
+            None => title,
+        }
+    }
+
+    fn haystack(&self) -> String {
+        [
+            self.title.as_deref(),
+            self.artist.as_deref(),
+            self.album.as_deref(),
+            self.path.file_name().and_then(|name| name.to_str()),
+        ]
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+    }
+}
+
+/// Scores how well `haystack` matches every token of the query.
+///
+/// Substring hits count more than hits where the token's characters merely
+/// appear in order, so `"dnt stp"` still finds "Don't Stop Me Now".
+fn score(tokens: &[String], haystack: &str) -> Option<usize> {
+    tokens.iter().try_fold(0, |score, token| {
+        if haystack.contains(token.as_str()) {
+            return Some(score + 2);
+        }
+
+        let mut chars = haystack.chars();
+        token
+            .chars()
+            .all(|c| chars.any(|h| h == c))
+            .then(|| score + 1)
+    })
+}
+
+pub struct MusicLibrary {
+    root: Option<PathBuf>,
+    tracks: Vec<LibraryTrack>,
+    indexing: bool,
+}
+
+impl TypeMapKey for MusicLibrary {
+    type Value = Arc<RwLock<MusicLibrary>>;
+}
+
+impl MusicLibrary {
+    pub fn new(root: Option<PathBuf>) -> Self {
+        Self {
+            root,
+            tracks: Vec::new(),
+            indexing: false,
+        }
+    }
+
+    /// Returns the library tracks matching `query`, best match first.
+    pub fn search(&self, query: &str) -> Vec<&LibraryTrack> {
+        let tokens = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        let mut matches = self
+            .tracks
+            .iter()
+            .filter_map(|track| score(&tokens, &track.haystack()).map(|score| (score, track)))
+            .collect::<Vec<_>>();
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        matches.into_iter().map(|(_, track)| track).collect()
+    }
+}
+
+async fn probe(path: PathBuf) -> LibraryTrack {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format"])
+        .arg(&path)
+        .output()
+        .await;
+    let format = output
+        .ok()
+        .and_then(|output| serde_json::from_slice::<Value>(&output.stdout).ok())
+        .and_then(|value| value.get("format").cloned())
+        .unwrap_or_default();
+
+    // Tag names differ in case between containers, e.g. `TITLE` in FLAC files.
+    let tags = format
+        .get("tags")
+        .and_then(Value::as_object)
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|(key, value)| Some((key.to_lowercase(), value.as_str()?.to_string())))
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    LibraryTrack {
+        title: tags.get("title").cloned(),
+        artist: tags.get("artist").cloned(),
+        album: tags.get("album").cloned(),
+        duration: format
+            .get("duration")
+            .and_then(Value::as_str)
+            .and_then(|duration| duration.parse::<f64>().ok())
+            .map(Duration::from_secs_f64),
+        path,
+    }
+}
+
+async fn index(root: &Path) -> Vec<LibraryTrack> {
+    let mut tracks = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let mut entries = match tokio::fs::read_dir(&directory).await {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let is_dir = entry.file_type().await.is_ok_and(|t| t.is_dir());
+
+            if is_dir {
+                directories.push(path);
+            } else if source::is_audio_file_name(&entry.file_name().to_string_lossy()) {
+                tracks.push(probe(path).await);
+            }
+        }
+    }
+
+    tracks.sort_by(|a, b| a.path.cmp(&b.path));
+    tracks
+}
+
+/// Walks the configured library directory and replaces the index with what is found there.
+pub async fn rebuild(library_lock: Arc<RwLock<MusicLibrary>>) {
+    let root = {
+        let mut library = library_lock.write().await;
+        if library.indexing {
+            return;
+        }
+        library.indexing = true;
+        library.root.clone()
+    };
+
+    let tracks = match root {
+        Some(root) => index(&root).await,
+        None => Vec::new(),
+    };
+
+    println!("Indexed {} tracks of the music library", tracks.len());
+
+    let mut library = library_lock.write().await;
+    library.tracks = tracks;
+    library.indexing = false;
+}
+
+async fn library_lock(ctx: &Context) -> Result<Arc<RwLock<MusicLibrary>>, Error> {
+    let library_lock = ctx
+        .data
+        .read()
+        .await
+        .get::<MusicLibrary>()
+        .ok_or(Error::Unknown)?
+        .clone();
+
+    if library_lock.read().await.root.is_none() {
+        Err(Error::LibraryNotConfigured)?;
+    }
+
+    Ok(library_lock)
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[min_args(1)]
+#[sub_commands(reindex)]
+async fn local(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+
+    let library_lock = library_lock(ctx).await?;
+    let (track, other_matches) = {
+        let library = library_lock.read().await;
+        let matches = library.search(query);
+
+        match matches.first() {
+            Some(track) => ((*track).clone(), matches.len() - 1),
+            None if library.indexing => Err(Error::LibraryIndexing)?,
+            None => Err(Error::NoSearchResults)?,
+        }
+    };
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+
+    let source = Source::File {
+        location: track.path.to_string_lossy().into_owned(),
+        name: track.display_name(),
+    };
+    player::enqueue_requested(ctx, guild_id, &handler_lock, source, Request::from_message(msg)).await?;
+    limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+
+    let duration = match track.duration {
+        Some(duration) => format!(" `{}`", format_duration(duration)),
+        None => String::new(),
+    };
+    msg.reply(
+        &ctx.http,
+        format!(
+            "Queued {}{} ({} other matches)",
+            track.display_name(),
+            duration,
+            other_matches
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+async fn reindex(ctx: &Context, _msg: &Message, _args: Args) -> CommandResult {
+    let library_lock = library_lock(ctx).await?;
+
+    if library_lock.read().await.indexing {
+        Err(Error::LibraryIndexing)?;
+    }
+
+    rebuild(library_lock).await;
+
+    Ok(())
+}
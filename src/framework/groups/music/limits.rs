@@ -0,0 +1,232 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{
+        channel::Message,
+        id::{GuildId, UserId},
+    },
+};
+use songbird::{tracks::TrackHandle, Call};
+use tokio::sync::Mutex;
+
+use crate::framework::error::Error;
+
+use super::request::{self, Request};
+use super::session;
+use super::settings::{self, GuildSettings};
+use super::source::{self, Source};
+use super::timestamp::{format_duration, parse_duration};
+use super::vote;
+
+/// Why tracks of a batch were turned away by the queue limits, each reason once.
+#[derive(Debug, Default)]
+pub struct Rejections {
+    count: usize,
+    reasons: Vec<String>,
+}
+
+impl Rejections {
+    /// Remembers `error` if it is a queue limit, returning whether it was one.
+    pub fn record(&mut self, error: &Error) -> bool {
+        let is_limit = matches!(
+            error,
+            Error::QueueFull(_)
+                | Error::UserTrackLimit(_)
+                | Error::TrackTooLong(_)
+                | Error::DuplicateTrack
+        );
+        if is_limit {
+            self.count += 1;
+            let reason = error.to_string();
+            if !self.reasons.contains(&reason) {
+                self.reasons.push(reason);
+            }
+        }
+
+        is_limit
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl fmt::Display for Rejections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reasons.join("; "))
+    }
+}
+
+/// Fails while the user's enqueue cooldown hasn't run out yet.
+pub async fn check_cooldown(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<(), Error> {
+    let cooldown = settings::get(ctx, guild_id).await.enqueue_cooldown();
+
+    if let Some(last) = session::get(ctx, guild_id).await.last_requests.get(&user_id) {
+        let elapsed = last.elapsed();
+        if elapsed < cooldown {
+            Err(Error::EnqueueCooldown((cooldown - elapsed).as_secs() + 1))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts the user's enqueue cooldown, once a command actually queued something.
+///
+/// Called once per command rather than per track, so importing a playlist counts as one request.
+pub async fn start_cooldown(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<(), Error> {
+    let cooldown = settings::get(ctx, guild_id).await.enqueue_cooldown();
+    if cooldown.is_zero() {
+        return Ok(());
+    }
+
+    session::update(ctx, guild_id, |s| {
+        s.last_requests.retain(|_, last| last.elapsed() < cooldown);
+        s.last_requests.insert(user_id, Instant::now());
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Checks whether `source` may join the guild's queue on behalf of the requester.
+pub async fn check(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: &Source,
+    request: &Request,
+) -> Result<(), Error> {
+    let guild_settings = settings::get(ctx, guild_id).await;
+    let queue = handler_lock.lock().await.queue().current_queue();
+
+    if guild_settings.max_queue_length > 0 && queue.len() >= guild_settings.max_queue_length {
+        Err(Error::QueueFull(guild_settings.max_queue_length))?;
+    }
+
+    let mut user_tracks = 0;
+    for track in queue.iter() {
+        if !guild_settings.allow_duplicates
+            && source::of(track)
+                .await
+                .is_some_and(|queued| queued.location() == source.location())
+        {
+            Err(Error::DuplicateTrack)?;
+        }
+        if request::of(track)
+            .await
+            .is_some_and(|queued| queued.user_id == request.user_id)
+        {
+            user_tracks += 1;
+        }
+    }
+
+    if guild_settings.max_user_tracks > 0 && user_tracks >= guild_settings.max_user_tracks {
+        Err(Error::UserTrackLimit(guild_settings.max_user_tracks))?;
+    }
+
+    Ok(())
+}
+
+/// Checks a created track's length, which only its metadata knows; live streams have none.
+pub fn check_duration(guild_settings: &GuildSettings, track: &TrackHandle) -> Result<(), Error> {
+    let max_duration = guild_settings.max_track_duration();
+    if max_duration.is_zero() {
+        return Ok(());
+    }
+
+    match track.metadata().duration {
+        Some(duration) if duration > max_duration => {
+            Err(Error::TrackTooLong(format_duration(max_duration)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn describe(guild_settings: &GuildSettings) -> String {
+    let limit = |value: usize| match value {
+        0 => "none".to_string(),
+        value => value.to_string(),
+    };
+    let time = |duration: Duration| match duration.is_zero() {
+        true => "none".to_string(),
+        false => format_duration(duration),
+    };
+
+    format!(
+        "Queue length: {}\nTracks per member: {}\nTrack duration: {}\nDuplicates: {}\nCooldown: {}",
+        limit(guild_settings.max_queue_length),
+        limit(guild_settings.max_user_tracks),
+        time(guild_settings.max_track_duration()),
+        if guild_settings.allow_duplicates { "allowed" } else { "rejected" },
+        time(guild_settings.enqueue_cooldown()),
+    )
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(2)]
+async fn limits(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if !args.is_empty() {
+        vote::require_dj(ctx, guild_id, msg.author.id).await?;
+
+        let usage = || {
+            Error::DetailedInvalidArguments(
+                "One of queue, user, duration, duplicates or cooldown and its new value, 0 for none".into(),
+            )
+        };
+        let limit = args.single::<String>()?.to_lowercase();
+        let value = args.single::<String>().map_err(|_| usage())?.to_lowercase();
+        let count = || value.parse::<usize>().map_err(|_| usage());
+        let time = || match value.as_str() {
+            "0" | "none" | "off" => Ok(0),
+            value => parse_duration(value).map(|duration| duration.as_secs()).ok_or_else(usage),
+        };
+
+        match limit.as_str() {
+            "queue" | "length" => {
+                let count = count()?;
+                settings::update(ctx, guild_id, |s| s.max_queue_length = count).await?
+            }
+            "user" | "member" => {
+                let count = count()?;
+                settings::update(ctx, guild_id, |s| s.max_user_tracks = count).await?
+            }
+            "duration" => {
+                let seconds = time()?;
+                settings::update(ctx, guild_id, |s| s.max_track_duration = seconds).await?
+            }
+            "duplicates" | "dupes" => {
+                let allow = match value.as_str() {
+                    "allow" | "on" | "yes" => true,
+                    "reject" | "off" | "no" => false,
+                    _ => Err(Error::DetailedInvalidArguments(
+                        "Duplicates are either allow or reject".into(),
+                    ))?,
+                };
+                settings::update(ctx, guild_id, |s| s.allow_duplicates = allow).await?
+            }
+            "cooldown" => {
+                let seconds = time()?;
+                settings::update(ctx, guild_id, |s| s.enqueue_cooldown = seconds).await?
+            }
+            _ => Err(usage())?,
+        };
+    }
+
+    let guild_settings = settings::get(ctx, guild_id).await;
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.title("Queue limits").description(describe(&guild_settings)))
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use serenity::{
+    async_trait,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{channel::Message, id::GuildId},
+};
+use songbird::{
+    Event, EventContext, EventHandler as VoiceEventHandler,
+    tracks::PlayMode,
+};
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::player;
+use super::request;
+use super::snapshot;
+use super::source;
+use super::session::{self, LoopMode};
+
+/// Puts every track that finished naturally back at the tail of the queue while
+/// the guild is in [`LoopMode::Queue`].
+pub struct QueueLooper {
+    pub ctx: Context,
+    pub guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for QueueLooper {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let &(state, track) = match ctx {
+            EventContext::Track(track_list) => track_list.first()?,
+            _ => return None,
+        };
+
+        // Skipped and stopped tracks end with `PlayMode::Stop` and are dropped for good.
+        if state.playing != PlayMode::End
+            || session::get(&self.ctx, self.guild_id).await.loop_mode != LoopMode::Queue
+        {
+            return None;
+        }
+
+        let source = source::of(track).await?;
+        let request = request::of(track).await?;
+        let handler_lock = songbird::get(&self.ctx).await?.get(self.guild_id)?;
+
+        player::enqueue(&self.ctx, self.guild_id, &handler_lock, source, request)
+            .await
+            .ok();
+
+        None
+    }
+}
+
+#[command("loop")]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[max_args(1)]
+#[aliases("repeat")]
+async fn loop_mode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if args.is_empty() {
+        let loop_mode = session::get(ctx, guild_id).await.loop_mode;
+        msg.reply(&ctx.http, format!("Loop mode is {}", loop_mode))
+            .await?;
+
+        return Ok(());
+    }
+
+    let loop_mode = args
+        .single::<String>()
+        .map_err(|_| Error::InvalidArguments)?
+        .parse::<LoopMode>()?;
+
+    session::update(ctx, guild_id, |s| s.loop_mode = loop_mode).await?;
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    // Track looping is a property of each track, so keep the whole queue in line with the mode.
+    if let Some(handler_lock) = manager.get(guild_id) {
+        for track_handle in handler_lock.lock().await.queue().current_queue() {
+            match loop_mode {
+                LoopMode::Track => track_handle.enable_loop(),
+                LoopMode::Off | LoopMode::Queue => track_handle.disable_loop(),
+            }
+            .map_err(|e| Error::Other(e.into()))?;
+        }
+    }
+    snapshot::save(ctx, guild_id).await?;
+
+    Ok(())
+}
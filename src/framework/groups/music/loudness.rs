@@ -0,0 +1,262 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::ErrorKind,
+    path::PathBuf,
+    process::Stdio,
+    sync::Arc,
+};
+
+use serde_json::Value;
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{channel::Message, id::GuildId},
+};
+use songbird::{tracks::TrackHandle, typemap::TypeMapKey};
+use tokio::{
+    process::Command,
+    sync::{RwLock, Semaphore},
+};
+
+use crate::framework::error::Error;
+
+use super::settings;
+use super::source::{self, Source};
+use super::vote;
+use super::ytdl;
+
+/// Integrated loudness in LUFS every track is brought to.
+const TARGET_LOUDNESS: f64 = -16.0;
+/// Loudness ReplayGain 2.0 tags are calculated against.
+const REPLAYGAIN_REFERENCE: f64 = -18.0;
+/// Quiet tracks are only raised this much so their noise floor doesn't blast.
+const MAX_GAIN_DB: f64 = 10.0;
+const MIN_GAIN_DB: f64 = -20.0;
+/// Analyses running at once, each one decodes a whole track.
+const CONCURRENT_ANALYSES: usize = 2;
+
+/// Measured gains in dB by source URL, kept across restarts since measuring is expensive.
+pub struct LoudnessCache {
+    path: PathBuf,
+    gains: HashMap<String, f64>,
+    pending: HashSet<String>,
+    analyses: Arc<Semaphore>,
+}
+
+impl TypeMapKey for LoudnessCache {
+    type Value = Arc<RwLock<LoudnessCache>>;
+}
+
+impl LoudnessCache {
+    pub async fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let gains = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::default(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self {
+            path,
+            gains,
+            pending: HashSet::new(),
+            analyses: Arc::new(Semaphore::new(CONCURRENT_ANALYSES)),
+        })
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(&self.gains)?).await?;
+
+        Ok(())
+    }
+}
+
+/// The gain a track was started with, so volume changes can keep it.
+struct TrackGain;
+
+impl TypeMapKey for TrackGain {
+    type Value = f32;
+}
+
+fn cache_key(source: &Source) -> Option<&str> {
+    match source {
+        Source::Ytdl { url } => Some(url),
+        Source::File { location, .. } => Some(location),
+        Source::Stream { .. } => None,
+    }
+}
+
+fn parse_db(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches("dB").trim().parse().ok()
+}
+
+/// Reads the ReplayGain track gain a local file has been tagged with.
+async fn replaygain(location: &str) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format"])
+        .arg(location)
+        .output()
+        .await
+        .ok()?;
+    let value = serde_json::from_slice::<Value>(&output.stdout).ok()?;
+
+    let gain = value
+        .get("format")?
+        .get("tags")?
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("replaygain_track_gain"))
+        .and_then(|(_, gain)| parse_db(gain.as_str()?))?;
+
+    Some(gain + TARGET_LOUDNESS - REPLAYGAIN_REFERENCE)
+}
+
+/// Runs the measuring pass of ffmpeg's EBU R128 `loudnorm` over the whole input.
+async fn loudnorm(input: &str) -> Option<f64> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-nostats", "-i", input, "-vn"])
+        .args(["-af", &format!("loudnorm=I={}:print_format=json", TARGET_LOUDNESS)])
+        .args(["-f", "null", "-"])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+
+    // The measurement is the last thing printed, after the usual ffmpeg chatter.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let value = serde_json::from_str::<Value>(&stderr[stderr.rfind('{')?..]).ok()?;
+    let loudness = parse_db(value.get("input_i")?.as_str()?)?;
+
+    // Digital silence measures as -inf and has no meaningful gain.
+    loudness.is_finite().then_some(TARGET_LOUDNESS - loudness)
+}
+
+async fn measure(source: &Source) -> Option<f64> {
+    match source {
+        Source::Ytdl { url } => loudnorm(&ytdl::stream_url(url).await?).await,
+        Source::File { location, .. } => match replaygain(location).await {
+            Some(gain) => Some(gain),
+            None => loudnorm(location).await,
+        },
+        Source::Stream { .. } => None,
+    }
+}
+
+/// Starts measuring `source` in the background unless its gain is known or being measured already.
+pub async fn analyse(ctx: &Context, source: &Source) {
+    let key = match cache_key(source) {
+        Some(key) => key.to_string(),
+        None => return,
+    };
+    let cache_lock = match ctx.data.read().await.get::<LoudnessCache>() {
+        Some(cache_lock) => cache_lock.clone(),
+        None => return,
+    };
+
+    let analyses = {
+        let mut cache = cache_lock.write().await;
+        if cache.gains.contains_key(&key) || !cache.pending.insert(key.clone()) {
+            return;
+        }
+        cache.analyses.clone()
+    };
+
+    let source = source.clone();
+    tokio::spawn(async move {
+        let gain = match analyses.acquire().await {
+            Ok(_permit) => measure(&source).await,
+            Err(_) => None,
+        };
+
+        let mut cache = cache_lock.write().await;
+        cache.pending.remove(&key);
+        if let Some(gain) = gain {
+            cache.gains.insert(key, gain.clamp(MIN_GAIN_DB, MAX_GAIN_DB));
+            cache.save().await.ok();
+        }
+    });
+}
+
+/// Looks up the measured gain of a track about to start and remembers it on the track.
+///
+/// Tracks play unaltered while normalization is off or their measurement isn't done yet.
+pub async fn start_gain(ctx: &Context, guild_id: GuildId, track: &TrackHandle) -> f32 {
+    if !settings::get(ctx, guild_id).await.normalize {
+        return 1.0;
+    }
+
+    let gain = match (ctx.data.read().await.get::<LoudnessCache>(), source::of(track).await) {
+        (Some(cache_lock), Some(source)) => match cache_key(&source) {
+            Some(key) => cache_lock.read().await.gains.get(key).copied(),
+            None => None,
+        },
+        _ => None,
+    };
+    let gain = gain.map_or(1.0, |gain| 10f64.powf(gain / 20.0) as f32);
+
+    track.typemap().write().await.insert::<TrackGain>(gain);
+
+    gain
+}
+
+/// The gain [`start_gain`] gave the track, `1.0` for tracks which haven't started.
+pub async fn gain(track: &TrackHandle) -> f32 {
+    track
+        .typemap()
+        .read()
+        .await
+        .get::<TrackGain>()
+        .copied()
+        .unwrap_or(1.0)
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+#[aliases("normalise")]
+async fn normalize(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    let normalize = if args.is_empty() {
+        settings::get(ctx, guild_id).await.normalize
+    } else {
+        vote::require_dj(ctx, guild_id, msg.author.id).await?;
+
+        let normalize = match args.single::<String>()?.to_lowercase().as_str() {
+            "on" | "true" | "enable" => true,
+            "off" | "false" | "disable" => false,
+            _ => Err(Error::DetailedInvalidArguments("on or off".into()))?,
+        };
+        settings::update(ctx, guild_id, |s| s.normalize = normalize).await?;
+
+        // Tracks queued while normalization was off haven't been measured.
+        let manager = songbird::get(ctx)
+            .await
+            .ok_or(Error::SongbirdInitialization)?
+            .clone();
+        if let (true, Some(handler_lock)) = (normalize, manager.get(guild_id)) {
+            let queue = handler_lock.lock().await.queue().current_queue();
+            for track in queue {
+                if let Some(source) = source::of(&track).await {
+                    analyse(ctx, &source).await;
+                }
+            }
+        }
+
+        normalize
+    };
+
+    let reply = if normalize {
+        "Loudness normalization is on, starting with the next track"
+    } else {
+        "Loudness normalization is off"
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,569 @@
+use std::sync::Arc;
+
+use serenity::{
+    async_trait,
+    client::{Context, EventHandler},
+    framework::standard::{
+        Args,
+        CommandResult, macros::{command, group},
+    },
+    http::Http,
+    model::{channel::Message, prelude::ChannelId},
+};
+use serenity::model::channel::Reaction;
+use serenity::model::gateway::Ready;
+use serenity::model::id::GuildId;
+use serenity::model::voice::VoiceState;
+use songbird::{Call, Event, EventContext, EventHandler as VoiceEventHandler, TrackEvent};
+use songbird::driver::Bitrate;
+use songbird::tracks::PlayMode;
+use tokio::sync::Mutex;
+
+use super::super::error::Error;
+
+use self::checks::{DJ_CHECK, SAMEVOICECHANNEL_CHECK};
+use self::fade::FADE_COMMAND;
+use self::filter::FILTER_COMMAND;
+use self::history::{HistoryRecorder, HISTORY_COMMAND, PREVIOUS_COMMAND, REPLAY_COMMAND};
+use self::idle::{AUTOLEAVE_COMMAND, IdleNotifier};
+use self::library::LOCAL_COMMAND;
+use self::limits::LIMITS_COMMAND;
+use self::loudness::NORMALIZE_COMMAND;
+use self::looping::{LOOP_MODE_COMMAND, QueueLooper};
+use self::nowplaying::NOWPLAYING_COMMAND;
+use self::playlist::PLAYLIST_COMMAND;
+use self::queue_edit::{
+    CLEAR_COMMAND, MOVE_TRACK_COMMAND, REMOVE_COMMAND, SHUFFLE_COMMAND, SKIPTO_COMMAND,
+};
+use self::queue_file::{EXPORT_COMMAND, IMPORT_COMMAND};
+use self::queue_list::LIST_COMMAND;
+use self::radio::RADIO_COMMAND;
+use self::request::Request;
+use self::snapshot::Snapshotter;
+use self::source::Source;
+use self::timestamp::{format_duration, SeekTarget};
+use self::volume::VOLUME_COMMAND;
+use self::vote::{DJ_COMMAND, SKIP_COMMAND, VOTESKIP_COMMAND};
+
+mod checks;
+mod fade;
+mod filter;
+pub mod history;
+mod idle;
+pub mod library;
+mod limits;
+mod looping;
+pub mod loudness;
+mod nowplaying;
+mod player;
+pub mod playlist;
+mod queue_edit;
+mod queue_file;
+pub mod queue_list;
+mod radio;
+mod request;
+mod search;
+pub mod session;
+pub mod settings;
+pub mod snapshot;
+mod source;
+mod timestamp;
+mod volume;
+mod vote;
+mod ytdl;
+
+pub struct Handler;
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, _ready: Ready) {
+        snapshot::restore(&ctx).await;
+    }
+
+    async fn reaction_add(&self, ctx: Context, add_reaction: Reaction) {
+        queue_list::turn_page(&ctx, &add_reaction).await;
+        vote::reaction_vote(&ctx, &add_reaction).await;
+    }
+
+    async fn voice_state_update(&self, ctx: Context, _old: Option<VoiceState>, new: VoiceState) {
+        if let Some(guild_id) = new.guild_id {
+            idle::leave_if_alone(&ctx, guild_id).await;
+        }
+    }
+}
+
+#[group]
+#[commands(
+    queue, local, radio, playlist, history, previous, replay, skip, voteskip, dj, skipto, seek, stop, pause, resume, volume, filter, fade, normalize, loop_mode, remove, move_track, shuffle,
+    clear, deafen, join, leave, autoleave, limits, mute, undeafen, unmute, nowplaying
+)]
+pub struct Music;
+
+struct TrackEndNotifier {
+    channel_id: ChannelId,
+    http: Arc<Http>,
+}
+
+#[async_trait]
+impl VoiceEventHandler for TrackEndNotifier {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(track_list) = ctx {
+            self.channel_id
+                .say(
+                    &self.http,
+                    &format!(
+                        "Tracks ended: {}.",
+                        track_list[0]
+                            .1
+                            .metadata()
+                            .clone()
+                            .title
+                            .unwrap_or_else(|| "Unknown".to_string())
+                    ),
+                )
+                .await
+                .ok()?;
+        }
+
+        None
+    }
+}
+
+#[command]
+async fn deafen(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+
+    let mut handler = handler_lock.lock().await;
+
+    if handler.is_deaf() {
+        Err(Error::AlreadyDeafened)?;
+    }
+
+    handler
+        .deafen(true)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+/// Joins the voice channel the author of `msg` is in and prepares the call for playback.
+async fn join_author_channel(ctx: &Context, msg: &Message) -> Result<Arc<Mutex<Call>>, Error> {
+    let guild = msg.guild(&ctx.cache).ok_or(Error::Unknown)?;
+
+    let channel_id = guild
+        .voice_states
+        .get(&msg.author.id)
+        .and_then(|state| state.channel_id)
+        .ok_or(Error::NotInVoiceChannel)?;
+
+    join_channel(ctx, guild.id, channel_id, msg.channel_id).await
+}
+
+/// Joins `channel_id` and prepares the call for playback, announcing to `text_channel_id`.
+async fn join_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    text_channel_id: ChannelId,
+) -> Result<Arc<Mutex<Call>>, Error> {
+    // The cache may not be filled yet when rejoining right after a restart.
+    let bitrate = match ctx.cache.guild_channel(channel_id) {
+        Some(guild_channel) => guild_channel.bitrate,
+        None => None,
+    };
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let (handle_lock, success) = manager.join(guild_id, channel_id).await;
+
+    success.map_err(|e| Error::Other(e.into()))?;
+
+    let send_http = ctx.http.clone();
+
+    let mut handle = handle_lock.lock().await;
+
+    // Joining again from another channel must not stack a second set of handlers.
+    handle.remove_all_global_events();
+
+    if let Some(bitrate) = bitrate {
+        handle.set_bitrate(Bitrate::BitsPerSecond(bitrate as i32));
+    }
+    handle.add_global_event(
+        Event::Track(TrackEvent::End),
+        TrackEndNotifier {
+            channel_id: text_channel_id,
+            http: send_http,
+        },
+    );
+
+    handle.add_global_event(
+        Event::Track(TrackEvent::End),
+        QueueLooper {
+            ctx: ctx.clone(),
+            guild_id,
+        },
+    );
+
+    handle.add_global_event(
+        Event::Track(TrackEvent::End),
+        HistoryRecorder {
+            ctx: ctx.clone(),
+            guild_id,
+        },
+    );
+
+    handle.add_global_event(
+        Event::Periodic(idle::CHECK_INTERVAL, None),
+        IdleNotifier {
+            ctx: ctx.clone(),
+            guild_id,
+            idle_checks: Default::default(),
+        },
+    );
+
+    handle.add_global_event(
+        Event::Track(TrackEvent::End),
+        Snapshotter {
+            ctx: ctx.clone(),
+            guild_id,
+        },
+    );
+    handle.add_global_event(
+        Event::Periodic(snapshot::SNAPSHOT_INTERVAL, None),
+        Snapshotter {
+            ctx: ctx.clone(),
+            guild_id,
+        },
+    );
+
+    drop(handle);
+
+    session::update(ctx, guild_id, |s| s.text_channel_id = Some(text_channel_id)).await?;
+
+    Ok(handle_lock)
+}
+
+/// Returns the guild's call, joining the author's voice channel first if the bot isn't connected.
+async fn connect(ctx: &Context, msg: &Message) -> Result<Arc<Mutex<Call>>, Error> {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        if handler_lock.lock().await.current_channel().is_some() {
+            return Ok(handler_lock);
+        }
+    }
+
+    join_author_channel(ctx, msg).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[aliases("j")]
+async fn join(ctx: &Context, msg: &Message) -> CommandResult {
+    join_author_channel(ctx, msg).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(DJ, SameVoiceChannel)]
+#[aliases("l")]
+async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+
+    manager
+        .remove(guild_id)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+    snapshot::forget(ctx, guild_id).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("m")]
+async fn mute(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+
+    let mut handler = handler_lock.lock().await;
+
+    if handler.is_mute() {
+        Err(Error::AlreadyMuted)?;
+    }
+    handler
+        .mute(true)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+async fn undeafen(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let mut handler = handler_lock.lock().await;
+
+    handler
+        .deafen(false)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+async fn unmute(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let mut handler = handler_lock.lock().await;
+
+    handler
+        .mute(false)
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[aliases("q")]
+#[sub_commands(list, export, import)]
+async fn queue(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+
+    // Without a query, play the audio files attached to the message or the one it replies to.
+    let attachments = match &msg.referenced_message {
+        Some(referenced) if msg.attachments.is_empty() => &referenced.attachments,
+        _ => &msg.attachments,
+    };
+    let files = attachments
+        .iter()
+        .filter_map(Source::from_attachment)
+        .collect::<Vec<_>>();
+
+    if query.is_empty() && files.is_empty() {
+        Err(Error::DetailedInvalidArguments(
+            "URL, search keywords or an audio attachment".into(),
+        ))?;
+    }
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+
+    if query.is_empty() {
+        for file in files {
+            player::enqueue_requested(ctx, guild_id, &handler_lock, file, Request::from_message(msg)).await?;
+            limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+        }
+
+        return Ok(());
+    }
+
+    if query.starts_with("http") && ytdl::is_playlist_url(query) {
+        let summary =
+            player::enqueue_playlist(ctx, guild_id, &handler_lock, query, Request::from_message(msg))
+                .await?;
+        if summary.queued > 0 {
+            limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+        }
+
+        let mut reply = format!("Queued {} tracks, {} skipped", summary.queued, summary.skipped);
+        if summary.rejections.count() > 0 {
+            reply.push_str(&format!(": {}", summary.rejections));
+        }
+        msg.reply(&ctx.http, reply).await?;
+
+        return Ok(());
+    }
+
+    let url = if query.starts_with("http") {
+        query.to_string()
+    } else {
+        search::select(ctx, msg, query).await?.url
+    };
+
+    player::enqueue_requested(ctx, guild_id, &handler_lock, Source::Ytdl { url }, Request::from_message(msg))
+        .await?;
+    limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(DJ, SameVoiceChannel)]
+#[aliases("s")]
+async fn stop(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let current = handler_lock.lock().await.queue().current();
+
+    // Drop everything queued right away and let only the playing track fade out.
+    handler_lock.lock().await.queue().modify_queue(|queue| {
+        for track in queue.drain(1.min(queue.len())..) {
+            track.stop().ok();
+        }
+    });
+    if let Some(current) = current {
+        fade::stop(&current, settings::get(ctx, guild_id).await.fade_out()).await?;
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+async fn pause(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    let queue = handler.queue();
+    let track_handle = queue.current().ok_or(Error::NothingPlaying)?;
+    let state = track_handle
+        .get_info()
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    if state.playing == PlayMode::Pause {
+        Err(Error::AlreadyPaused)?;
+    }
+
+    queue.pause().map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[aliases("unpause")]
+async fn resume(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    let queue = handler.queue();
+    let track_handle = queue.current().ok_or(Error::NothingPlaying)?;
+    let state = track_handle
+        .get_info()
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    if state.playing != PlayMode::Pause {
+        Err(Error::NotPaused)?;
+    }
+
+    queue.resume().map_err(|e| Error::Other(e.into()))?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[num_args(1)]
+async fn seek(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = args
+        .single::<String>()
+        .map_err(|_| Error::InvalidArguments)?
+        .parse::<SeekTarget>()?;
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    let queue = handler.queue();
+
+    let track_handle = queue.current().ok_or(Error::NothingPlaying)?;
+
+    let is_stream = source::of(&track_handle)
+        .await
+        .is_some_and(|source| !source.is_seekable());
+    if is_stream || !track_handle.is_seekable() {
+        Err(Error::NotSeekable)?;
+    }
+
+    let position = track_handle
+        .get_info()
+        .await
+        .map_err(|e| Error::Other(e.into()))?
+        .position;
+    let position = target.resolve(player::source_position(&track_handle, position).await);
+
+    player::seek(&track_handle, position).await?;
+
+    msg.reply(&ctx.http, format!("Seeked to {}", format_duration(position)))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    framework::standard::{CommandResult, macros::command},
+    model::channel::Message,
+};
+use songbird::{
+    input::Metadata,
+    tracks::{PlayMode, TrackState},
+};
+
+use crate::framework::error::Error;
+
+use super::radio;
+use super::request::{self, Request};
+use super::session::{self, LoopMode};
+use super::timestamp::format_duration;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+const PROGRESS_BAR_LENGTH: usize = 20;
+
+fn progress_bar(position: Duration, duration: Option<Duration>) -> String {
+    let duration = match duration {
+        Some(duration) if !duration.is_zero() => duration,
+        _ => return format!("🔴 LIVE `{}`", format_duration(position)),
+    };
+
+    let ratio = (position.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0);
+    let filled = ((PROGRESS_BAR_LENGTH - 1) as f64 * ratio).round() as usize;
+
+    format!(
+        "{}🔘{} `{} / {}`",
+        "▬".repeat(filled),
+        "▬".repeat(PROGRESS_BAR_LENGTH - 1 - filled),
+        format_duration(position),
+        format_duration(duration),
+    )
+}
+
+pub fn now_playing_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    metadata: &Metadata,
+    state: &TrackState,
+    request: Option<Request>,
+    loop_mode: LoopMode,
+) -> &'a mut CreateEmbed {
+    embed
+        .title(metadata.title.clone().unwrap_or_else(|| "Unknown".to_string()))
+        .description(format!(
+            "{} {}",
+            if state.playing == PlayMode::Pause { "⏸️" } else { "▶️" },
+            progress_bar(state.position, metadata.duration)
+        ));
+
+    if let Some(url) = &metadata.source_url {
+        embed.url(url);
+    }
+    if let Some(thumbnail) = &metadata.thumbnail {
+        embed.thumbnail(thumbnail);
+    }
+    if let Some(artist) = metadata.artist.as_ref().or(metadata.channel.as_ref()) {
+        embed.field("Artist", artist, true);
+    }
+    if let Some(request) = request {
+        embed
+            .field(
+                "Requested by",
+                format!("<@{}> in <#{}>", request.user_id, request.channel_id),
+                true,
+            )
+            .timestamp(request.requested_at);
+    }
+    if loop_mode != LoopMode::Off {
+        embed.footer(|f| f.text(format!("🔁 Loop: {}", loop_mode)));
+    }
+
+    embed
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("np")]
+async fn nowplaying(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let track_handle = handler_lock
+        .lock()
+        .await
+        .queue()
+        .current()
+        .ok_or(Error::NothingPlaying)?;
+
+    let metadata = radio::metadata(&track_handle).await;
+    let request = request::of(&track_handle).await;
+    let loop_mode = session::get(ctx, guild_id).await.loop_mode;
+    let state = track_handle
+        .get_info()
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    let mut message = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| now_playing_embed(e, &metadata, &state, request, loop_mode))
+        })
+        .await?;
+
+    // Keep the progress bar moving until the track is gone or the message can't be edited anymore.
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+
+            let state = match track_handle.get_info().await {
+                Ok(state) if !state.playing.is_done() => state,
+                _ => break,
+            };
+            let loop_mode = session::get(&ctx, guild_id).await.loop_mode;
+            let metadata = radio::metadata(&track_handle).await;
+
+            if message
+                .edit(&ctx, |m| {
+                    m.embed(|e| now_playing_embed(e, &metadata, &state, request, loop_mode))
+                })
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
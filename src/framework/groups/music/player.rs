@@ -0,0 +1,336 @@
+use std::time::Duration;
+
+use serenity::{async_trait, client::Context, model::id::GuildId};
+use songbird::{
+    create_player,
+    input::Metadata,
+    tracks::{LoopState, PlayMode, TrackHandle},
+    typemap::TypeMapKey,
+    Call, Event, EventContext, EventHandler as VoiceEventHandler,
+};
+use tokio::sync::Mutex;
+
+use crate::framework::error::Error;
+
+use super::fade::{self, Outro};
+use super::filter;
+use super::history;
+use super::limits;
+use super::loudness;
+use super::radio::{IcyWatcher, ICY_WATCH_DELAY};
+use super::request::{self, Request};
+use super::session::{self, LoopMode};
+use super::settings;
+use super::snapshot;
+use super::source::{self, Source};
+use super::ytdl;
+
+const MAX_PLAYLIST_TRACKS: usize = 50;
+
+/// Outcome of [`enqueue_playlist`], reported back to the requester.
+pub struct PlaylistSummary {
+    pub queued: usize,
+    pub skipped: usize,
+    pub rejections: limits::Rejections,
+}
+
+/// Where a track's playback position last matched a point in its source. Tempo changing
+/// filters make the two drift apart, the position only counts the audio played out.
+#[derive(Clone, Copy, Debug)]
+struct SourceClock {
+    position: Duration,
+    source: Duration,
+    tempo: f64,
+}
+
+impl TypeMapKey for SourceClock {
+    type Value = SourceClock;
+}
+
+/// Translates the track's playback `position` into how far into its source it is.
+pub async fn source_position(track: &TrackHandle, position: Duration) -> Duration {
+    match track.typemap().read().await.get::<SourceClock>() {
+        Some(clock) => clock.source + position.saturating_sub(clock.position).mul_f64(clock.tempo),
+        None => position,
+    }
+}
+
+/// Seeks the track to `target` within its source.
+pub async fn seek(track: &TrackHandle, target: Duration) -> Result<(), Error> {
+    track.seek_time(target).map_err(|e| Error::Other(e.into()))?;
+
+    // Seeking restarts the source there, and the position with it.
+    if let Some(clock) = track.typemap().write().await.get_mut::<SourceClock>() {
+        clock.position = target;
+        clock.source = target;
+    }
+
+    Ok(())
+}
+
+/// Volume percentage a track plays at instead of the guild's, as restored from a snapshot.
+struct VolumeOverride;
+
+impl TypeMapKey for VolumeOverride {
+    type Value = u16;
+}
+
+/// Makes the track play at `volume` percent regardless of the guild's setting, or follow
+/// the setting again for `None`.
+pub async fn override_volume(track: &TrackHandle, volume: Option<u16>) {
+    let mut typemap = track.typemap().write().await;
+    match volume {
+        Some(volume) => typemap.insert::<VolumeOverride>(volume),
+        None => {
+            typemap.remove::<VolumeOverride>();
+        }
+    }
+}
+
+/// The volume percentage the track plays at instead of the guild's, if any.
+pub async fn volume_override(track: &TrackHandle) -> Option<u16> {
+    track.typemap().read().await.get::<VolumeOverride>().copied()
+}
+
+/// Sets the volume of a track as it starts playing, by which time its
+/// loudness has most likely been measured.
+struct TrackStart {
+    ctx: Context,
+    guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for TrackStart {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let &(_, track) = match ctx {
+            EventContext::Track(track_list) => track_list.first()?,
+            _ => return None,
+        };
+
+        let guild_settings = settings::get(&self.ctx, self.guild_id).await;
+        let volume = match volume_override(track).await {
+            Some(volume) => f32::from(volume) / 100.0,
+            None => guild_settings.track_volume(),
+        } * loudness::start_gain(&self.ctx, self.guild_id, track).await;
+
+        // A crossfaded track has to come in as gently as the previous one goes.
+        let fade_in = guild_settings.fade_in().max(guild_settings.crossfade());
+        if fade_in.is_zero() {
+            track.set_volume(volume).ok();
+        } else {
+            fade::ramp(track, 0.0, volume, fade_in, false).ok();
+        }
+
+        None
+    }
+}
+
+/// Appends `source` to the guild's queue, applying the guild's current
+/// playback preferences to the new track.
+pub async fn enqueue(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: Source,
+    request: Request,
+) -> Result<TrackHandle, Error> {
+    add(ctx, guild_id, handler_lock, source, request, None, false).await
+}
+
+/// Like [`enqueue`], but for tracks members ask for, which have to keep within the guild's
+/// queue limits. Tracks the bot queues up again itself are exempt.
+pub async fn enqueue_requested(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: Source,
+    request: Request,
+) -> Result<TrackHandle, Error> {
+    limits::check(ctx, guild_id, handler_lock, &source, &request).await?;
+
+    add(ctx, guild_id, handler_lock, source, request, None, true).await
+}
+
+/// Like [`enqueue_requested`], for tracks whose metadata is already known from a listing
+/// such as a playlist, so nothing is looked up per track until it actually plays.
+pub async fn enqueue_listed(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: Source,
+    request: Request,
+    metadata: Metadata,
+) -> Result<TrackHandle, Error> {
+    limits::check(ctx, guild_id, handler_lock, &source, &request).await?;
+
+    add(ctx, guild_id, handler_lock, source, request, Some(metadata), true).await
+}
+
+async fn add(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: Source,
+    request: Request,
+    metadata: Option<Metadata>,
+    limited: bool,
+) -> Result<TrackHandle, Error> {
+    let guild_settings = settings::get(ctx, guild_id).await;
+    let (mut track, track_handle) =
+        create_player(source.input(filter::chain(&guild_settings.filters), metadata).await?);
+    if limited {
+        limits::check_duration(&guild_settings, &track_handle)?;
+    }
+
+    // The real volume is only known once the track starts, see `TrackStart`.
+    track.set_volume(0.0);
+    track_handle
+        .add_event(Event::Delayed(Duration::ZERO), TrackStart { ctx: ctx.clone(), guild_id })
+        .map_err(|e| Error::Other(e.into()))?;
+    if guild_settings.normalize {
+        loudness::analyse(ctx, &source).await;
+    }
+
+    let outro = Outro {
+        ctx: ctx.clone(),
+        guild_id,
+        fade_out: guild_settings.fade_out(),
+        crossfade: guild_settings.crossfade(),
+        faded: Default::default(),
+    };
+    if let (Some(duration), true) = (track_handle.metadata().duration, source.is_seekable()) {
+        if !outro.fade_out.is_zero() || !outro.crossfade.is_zero() {
+            track_handle
+                .add_event(outro.schedule(duration), outro)
+                .map_err(|e| Error::Other(e.into()))?;
+        }
+    }
+    if session::get(ctx, guild_id).await.loop_mode == LoopMode::Track {
+        track
+            .set_loops(LoopState::Infinite)
+            .map_err(|e| Error::Other(e.into()))?;
+    }
+    if let Source::Stream { url, .. } = &source {
+        track_handle
+            .add_event(Event::Delayed(ICY_WATCH_DELAY), IcyWatcher { url: url.clone() })
+            .map_err(|e| Error::Other(e.into()))?;
+    }
+    request::attach(&track_handle, request).await;
+    source::attach(&track_handle, source).await;
+    track_handle.typemap().write().await.insert::<SourceClock>(SourceClock {
+        position: Duration::ZERO,
+        source: Duration::ZERO,
+        tempo: filter::tempo(&guild_settings.filters),
+    });
+
+    handler_lock.lock().await.enqueue(track);
+    snapshot::save(ctx, guild_id).await.ok();
+
+    Ok(track_handle)
+}
+
+/// Like [`enqueue_requested`], but puts the new track right after the one playing.
+pub async fn enqueue_next(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    source: Source,
+    request: Request,
+) -> Result<TrackHandle, Error> {
+    let track_handle = enqueue_requested(ctx, guild_id, handler_lock, source, request).await?;
+
+    handler_lock.lock().await.queue().modify_queue(|queue| {
+        if queue.len() > 2 {
+            if let Some(track) = queue.pop_back() {
+                queue.insert(1, track);
+            }
+        }
+    });
+    snapshot::save(ctx, guild_id).await.ok();
+
+    Ok(track_handle)
+}
+
+/// Replaces the playing track with a fresh copy created with the guild's current
+/// preferences, picking up where the old one was.
+pub async fn restart_current(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+) -> Result<(), Error> {
+    let current = match handler_lock.lock().await.queue().current() {
+        Some(current) => current,
+        None => return Ok(()),
+    };
+    let (source, request) = match (source::of(&current).await, request::of(&current).await) {
+        (Some(source), Some(request)) => (source, request),
+        _ => return Ok(()),
+    };
+    let state = current.get_info().await.map_err(|e| Error::Other(e.into()))?;
+    // The copy may play at another tempo, so it has to pick up where the source was.
+    let position = source_position(&current, state.position).await;
+
+    let track_handle = enqueue(ctx, guild_id, handler_lock, source.clone(), request).await?;
+    if source.is_seekable() {
+        seek(&track_handle, position).await?;
+    }
+
+    // Swap the head in place; skipping would race the queue's own end handler
+    // and couldn't keep a paused track paused.
+    handler_lock.lock().await.queue().modify_queue(|queue| {
+        if let Some(track) = queue.pop_back() {
+            queue.pop_front();
+            queue.push_front(track);
+        }
+    });
+    history::supersede(&current).await;
+    current.stop().ok();
+    if state.playing != PlayMode::Pause {
+        track_handle.play().map_err(|e| Error::Other(e.into()))?;
+    }
+
+    Ok(())
+}
+
+/// Expands a playlist URL and enqueues each of its entries as a separate lazy track,
+/// importing at most [`MAX_PLAYLIST_TRACKS`] of them.
+///
+/// Entries are queued with what the listing says about them, so nothing is looked up
+/// per entry until it actually plays.
+pub async fn enqueue_playlist(
+    ctx: &Context,
+    guild_id: GuildId,
+    handler_lock: &Mutex<Call>,
+    url: &str,
+    request: Request,
+) -> Result<PlaylistSummary, Error> {
+    let mut summary = PlaylistSummary {
+        queued: 0,
+        skipped: 0,
+        rejections: Default::default(),
+    };
+
+    for entry in ytdl::playlist(url, MAX_PLAYLIST_TRACKS).await? {
+        let enqueued = match entry {
+            Some(entry) => {
+                let source = Source::Ytdl { url: entry.url.clone() };
+                match enqueue_listed(ctx, guild_id, handler_lock, source, request, entry.metadata()).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        summary.rejections.record(&e);
+                        false
+                    }
+                }
+            }
+            None => false,
+        };
+
+        if enqueued {
+            summary.queued += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,503 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{
+        channel::Message,
+        id::{GuildId, RoleId, UserId},
+    },
+};
+use songbird::{input::Metadata, typemap::TypeMapKey};
+use tokio::sync::RwLock;
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::limits;
+use super::request::Request;
+use super::search;
+use super::settings;
+use super::source::{self, Source};
+use super::timestamp::format_duration;
+use super::ytdl;
+use super::{connect, player};
+
+const MAX_PLAYLIST_TRACKS: usize = 500;
+const SHOWN_TRACKS: usize = 20;
+const PLAYLIST_USAGE: &str = "Playlist name, prefixed with `guild` for the guild's shared playlists";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlaylistTrack {
+    source: Source,
+    title: Option<String>,
+    duration: Option<Duration>,
+}
+
+impl PlaylistTrack {
+    /// What was saved about the track, enough to queue it without looking it up again.
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            title: self.title.clone(),
+            duration: self.duration,
+            source_url: Some(self.source.location().to_string()).filter(|url| source::is_web_url(url)),
+            ..Default::default()
+        }
+    }
+
+    fn line(&self) -> String {
+        let title = self.title.clone().unwrap_or_else(|| "Unknown".to_string());
+        let title = match &self.source {
+            Source::Ytdl { url } | Source::Stream { url, .. } => format!("[{}]({})", title, url),
+            Source::File { .. } => title,
+        };
+
+        match self.duration {
+            Some(duration) => format!("{} `{}`", title, format_duration(duration)),
+            None => title,
+        }
+    }
+}
+
+/// Whose playlist it is: one member's own or shared by everyone in a guild.
+#[derive(Clone, Copy, Debug)]
+enum Scope {
+    Personal(UserId),
+    Guild(GuildId),
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scope::Personal(_) => write!(f, "personal"),
+            Scope::Guild(_) => write!(f, "guild"),
+        }
+    }
+}
+
+/// Saved playlists by owner and name, kept in a JSON file next to the guild settings.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct PlaylistLists {
+    personal: HashMap<u64, BTreeMap<String, Vec<PlaylistTrack>>>,
+    guilds: HashMap<u64, BTreeMap<String, Vec<PlaylistTrack>>>,
+}
+
+pub struct Playlists {
+    path: PathBuf,
+    lists: PlaylistLists,
+}
+
+impl TypeMapKey for Playlists {
+    type Value = Arc<RwLock<Playlists>>;
+}
+
+impl Playlists {
+    pub async fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let lists = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => PlaylistLists::default(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self { path, lists })
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(&self.lists)?).await?;
+
+        Ok(())
+    }
+
+    fn owned(&self, scope: Scope) -> Option<&BTreeMap<String, Vec<PlaylistTrack>>> {
+        match scope {
+            Scope::Personal(user_id) => self.lists.personal.get(&user_id.0),
+            Scope::Guild(guild_id) => self.lists.guilds.get(&guild_id.0),
+        }
+    }
+
+    fn owned_mut(&mut self, scope: Scope) -> &mut BTreeMap<String, Vec<PlaylistTrack>> {
+        match scope {
+            Scope::Personal(user_id) => self.lists.personal.entry(user_id.0).or_default(),
+            Scope::Guild(guild_id) => self.lists.guilds.entry(guild_id.0).or_default(),
+        }
+    }
+
+    fn get(&self, scope: Scope, name: &str) -> Option<&Vec<PlaylistTrack>> {
+        self.owned(scope)?.get(name)
+    }
+}
+
+async fn playlists_lock(ctx: &Context) -> Result<Arc<RwLock<Playlists>>, Error> {
+    Ok(ctx
+        .data
+        .read()
+        .await
+        .get::<Playlists>()
+        .ok_or(Error::Unknown)?
+        .clone())
+}
+
+/// Reads `[guild] <name>` off the arguments, names being case insensitive.
+fn scope_and_name(msg: &Message, args: &mut Args) -> Result<(Scope, String), Error> {
+    let usage = || Error::DetailedInvalidArguments(PLAYLIST_USAGE.into());
+
+    let mut name = args.single_quoted::<String>().map_err(|_| usage())?;
+    let scope = if name.eq_ignore_ascii_case("guild") && !args.is_empty() {
+        name = args.single_quoted::<String>().map_err(|_| usage())?;
+        Scope::Guild(msg.guild_id.ok_or(Error::Unknown)?)
+    } else {
+        Scope::Personal(msg.author.id)
+    };
+
+    Ok((scope, name.to_lowercase()))
+}
+
+/// Personal playlists belong to their owner alone; guild playlists may be changed by
+/// members with the guild's playlist role or the permission to manage the guild.
+async fn check_editable(ctx: &Context, msg: &Message, scope: Scope) -> Result<(), Error> {
+    let guild_id = match scope {
+        Scope::Personal(_) => return Ok(()),
+        Scope::Guild(guild_id) => guild_id,
+    };
+
+    let member = msg.member(ctx).await.map_err(|e| Error::Other(e.into()))?;
+    let playlist_role = settings::get(ctx, guild_id).await.playlist_role;
+    if playlist_role.is_some_and(|role_id| member.roles.contains(&role_id)) {
+        return Ok(());
+    }
+
+    let can_manage = member
+        .permissions(&ctx.cache)
+        .is_ok_and(|permissions| permissions.manage_guild());
+    if !can_manage {
+        Err(Error::PlaylistNotEditable)?;
+    }
+
+    Ok(())
+}
+
+/// Resolves what to add to a playlist: the playing track, a URL or the pick of a search.
+async fn resolve_track(ctx: &Context, msg: &Message, query: &str) -> Result<PlaylistTrack, Error> {
+    if query.eq_ignore_ascii_case("current") {
+        let guild_id = msg.guild_id.ok_or(Error::Unknown)?;
+        let manager = songbird::get(ctx)
+            .await
+            .ok_or(Error::SongbirdInitialization)?
+            .clone();
+        let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+        let current = handler_lock
+            .lock()
+            .await
+            .queue()
+            .current()
+            .ok_or(Error::NothingPlaying)?;
+
+        let metadata = current.metadata();
+        return Ok(PlaylistTrack {
+            source: source::of(&current).await.ok_or(Error::Unknown)?,
+            title: metadata.title.clone(),
+            duration: metadata.duration,
+        });
+    }
+
+    if query.starts_with("http") {
+        let metadata = ytdl::metadata(query).await.ok();
+        return Ok(PlaylistTrack {
+            source: Source::Ytdl {
+                url: query.to_string(),
+            },
+            title: metadata.as_ref().and_then(|metadata| metadata.title.clone()),
+            duration: metadata.and_then(|metadata| metadata.duration),
+        });
+    }
+
+    let entry = search::select(ctx, msg, query).await?;
+    Ok(PlaylistTrack {
+        source: Source::Ytdl { url: entry.url },
+        title: entry.title,
+        duration: entry.duration,
+    })
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("pl")]
+#[sub_commands(create, add, remove, show, play, delete, role)]
+async fn playlist(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.ok_or(Error::Unknown)?;
+    let playlists_lock = playlists_lock(ctx).await?;
+    let playlists = playlists_lock.read().await;
+
+    let list = |scope: Scope| match playlists.owned(scope) {
+        Some(owned) if !owned.is_empty() => owned
+            .iter()
+            .map(|(name, tracks)| format!("`{}` ({} tracks)", name, tracks.len()))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => "None".to_string(),
+    };
+    let personal = list(Scope::Personal(msg.author.id));
+    let guild = list(Scope::Guild(guild_id));
+    drop(playlists);
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Playlists")
+                    .field("Yours", personal, false)
+                    .field("This guild's", guild, false)
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(1)]
+#[aliases("new")]
+async fn create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (scope, name) = scope_and_name(msg, &mut args)?;
+    check_editable(ctx, msg, scope).await?;
+
+    let playlists_lock = playlists_lock(ctx).await?;
+    let mut playlists = playlists_lock.write().await;
+
+    if playlists.get(scope, &name).is_some() {
+        Err(Error::PlaylistExists(name.clone()))?;
+    }
+    playlists.owned_mut(scope).insert(name.clone(), Vec::new());
+    playlists.save().await?;
+    drop(playlists);
+
+    msg.reply(&ctx.http, format!("Created {} playlist `{}`", scope, name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(2)]
+async fn add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (scope, name) = scope_and_name(msg, &mut args)?;
+    check_editable(ctx, msg, scope).await?;
+
+    let playlists_lock = playlists_lock(ctx).await?;
+    if playlists_lock.read().await.get(scope, &name).is_none() {
+        Err(Error::PlaylistNotFound(name.clone()))?;
+    }
+
+    // Searching can take a while, so the playlist is only locked once we know what to add.
+    let track = resolve_track(ctx, msg, args.rest().trim()).await?;
+    let line = track.line();
+
+    let mut playlists = playlists_lock.write().await;
+    let tracks = playlists
+        .owned_mut(scope)
+        .get_mut(&name)
+        .ok_or_else(|| Error::PlaylistNotFound(name.clone()))?;
+    if tracks.len() >= MAX_PLAYLIST_TRACKS {
+        Err(Error::DetailedInvalidArguments(format!(
+            "Playlists hold at most {} tracks",
+            MAX_PLAYLIST_TRACKS
+        )))?;
+    }
+    tracks.push(track);
+    playlists.save().await?;
+    drop(playlists);
+
+    msg.reply(&ctx.http, format!("Added {} to `{}`", line, name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(2)]
+#[aliases("rm")]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (scope, name) = scope_and_name(msg, &mut args)?;
+    let position = args.single::<usize>().map_err(|_| {
+        Error::DetailedInvalidArguments("Playlist name and the number of the track to remove".into())
+    })?;
+    check_editable(ctx, msg, scope).await?;
+
+    let playlists_lock = playlists_lock(ctx).await?;
+    let mut playlists = playlists_lock.write().await;
+    let tracks = playlists
+        .owned_mut(scope)
+        .get_mut(&name)
+        .ok_or_else(|| Error::PlaylistNotFound(name.clone()))?;
+
+    if position == 0 || position > tracks.len() {
+        Err(Error::PlaylistPositionOutOfRange(name.clone(), position))?;
+    }
+    let track = tracks.remove(position - 1);
+    playlists.save().await?;
+    drop(playlists);
+
+    msg.reply(&ctx.http, format!("Removed {} from `{}`", track.line(), name))
+        .await?;
+
+    Ok(())
+}
+
+/// Finds a playlist to read, trying the author's own before the guild's shared ones.
+async fn find(ctx: &Context, msg: &Message, args: &mut Args) -> Result<(String, Vec<PlaylistTrack>), Error> {
+    let (scope, name) = scope_and_name(msg, args)?;
+    let playlists_lock = playlists_lock(ctx).await?;
+    let playlists = playlists_lock.read().await;
+
+    let tracks = match scope {
+        Scope::Personal(_) => playlists
+            .get(scope, &name)
+            .or_else(|| playlists.get(Scope::Guild(msg.guild_id?), &name)),
+        Scope::Guild(_) => playlists.get(scope, &name),
+    };
+
+    match tracks {
+        Some(tracks) => Ok((name, tracks.clone())),
+        None => Err(Error::PlaylistNotFound(name)),
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(1)]
+#[aliases("ls")]
+async fn show(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (name, tracks) = find(ctx, msg, &mut args).await?;
+
+    let mut description = tracks
+        .iter()
+        .take(SHOWN_TRACKS)
+        .enumerate()
+        .map(|(index, track)| format!("`{}.` {}", index + 1, track.line()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if tracks.len() > SHOWN_TRACKS {
+        description.push_str(&format!("\n… and {} more", tracks.len() - SHOWN_TRACKS));
+    }
+    if tracks.is_empty() {
+        description = "Nothing in here yet".to_string();
+    }
+
+    let total = tracks.iter().filter_map(|track| track.duration).sum::<Duration>();
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title(&name).description(description).footer(|f| {
+                    f.text(format!("{} tracks · {}", tracks.len(), format_duration(total)))
+                })
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[min_args(1)]
+#[aliases("p")]
+async fn play(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (name, tracks) = find(ctx, msg, &mut args).await?;
+    let guild_id = msg.guild_id.ok_or(Error::Unknown)?;
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+
+    let mut queued = 0;
+    let mut rejections = limits::Rejections::default();
+    for track in tracks.iter() {
+        let (source, request) = (track.source.clone(), Request::from_message(msg));
+        match player::enqueue_listed(ctx, guild_id, &handler_lock, source, request, track.metadata()).await {
+            Ok(_) => queued += 1,
+            Err(e) => {
+                rejections.record(&e);
+            }
+        }
+    }
+
+    if queued > 0 {
+        limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+    }
+
+    let mut reply = format!(
+        "Queued {} tracks from `{}`, {} skipped",
+        queued,
+        name,
+        tracks.len() - queued
+    );
+    if rejections.count() > 0 {
+        reply.push_str(&format!(": {}", rejections));
+    }
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(1)]
+async fn delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (scope, name) = scope_and_name(msg, &mut args)?;
+    check_editable(ctx, msg, scope).await?;
+
+    let playlists_lock = playlists_lock(ctx).await?;
+    let mut playlists = playlists_lock.write().await;
+    if playlists.owned_mut(scope).remove(&name).is_none() {
+        Err(Error::PlaylistNotFound(name.clone()))?;
+    }
+    playlists.save().await?;
+    drop(playlists);
+
+    msg.reply(&ctx.http, format!("Deleted {} playlist `{}`", scope, name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+#[required_permissions("MANAGE_GUILD")]
+async fn role(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.ok_or(Error::Unknown)?;
+
+    if !args.is_empty() {
+        let playlist_role = match args.single::<String>()?.as_str() {
+            "none" | "off" => None,
+            role => Some(role.parse::<RoleId>().map_err(|_| {
+                Error::DetailedInvalidArguments("A role mention or id, or none".into())
+            })?),
+        };
+        settings::update(ctx, guild_id, |s| s.playlist_role = playlist_role).await?;
+    }
+
+    let reply = match settings::get(ctx, guild_id).await.playlist_role {
+        Some(role_id) => format!("Guild playlists can be edited by <@&{}>", role_id),
+        None => "Guild playlists can only be edited by guild managers".to_string(),
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use rand::seq::SliceRandom;
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::Message,
+};
+
+use crate::framework::error::Error;
+
+use super::checks::{DJ_CHECK, SAMEVOICECHANNEL_CHECK};
+use super::fade;
+use super::settings;
+use super::snapshot;
+
+// Positions are the ones shown by `queue list`: 0 is the playing track, 1 is up next.
+
+fn position(args: &mut Args) -> Result<usize, Error> {
+    args.single::<usize>().map_err(|_| Error::InvalidArguments)
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[num_args(1)]
+#[aliases("rm")]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let index = position(&mut args)?;
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    let removed = handler
+        .queue()
+        .modify_queue(|q| match index {
+            0 => None,
+            _ => q.remove(index),
+        })
+        .ok_or(Error::QueuePositionOutOfRange(index))?;
+    removed.stop().ok();
+
+    msg.reply(
+        &ctx.http,
+        format!(
+            "Removed {}",
+            removed.metadata().title.as_deref().unwrap_or("Unknown")
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command("move")]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[num_args(2)]
+#[aliases("mv")]
+async fn move_track(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let from = position(&mut args)?;
+    let to = position(&mut args)?;
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    handler.queue().modify_queue(|q| {
+        for index in [from, to] {
+            if index == 0 || index >= q.len() {
+                return Err(Error::QueuePositionOutOfRange(index));
+            }
+        }
+
+        let track = q.remove(from).ok_or(Error::QueuePositionOutOfRange(from))?;
+        q.insert(to, track);
+
+        Ok(())
+    })?;
+    drop(handler);
+
+    snapshot::save(ctx, guild_id).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+async fn shuffle(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    handler.queue().modify_queue(|q| {
+        if let Some((_, upcoming)) = q.make_contiguous().split_first_mut() {
+            upcoming.shuffle(&mut rand::thread_rng());
+        }
+    });
+    drop(handler);
+
+    snapshot::save(ctx, guild_id).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(DJ, SameVoiceChannel)]
+async fn clear(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let handler = handler_lock.lock().await;
+
+    let removed = handler
+        .queue()
+        .modify_queue(|q| q.drain(1.min(q.len())..).collect::<Vec<_>>());
+    for track in removed {
+        track.stop().ok();
+    }
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(DJ, SameVoiceChannel)]
+#[num_args(1)]
+async fn skipto(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let index = position(&mut args)?;
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let (current, skipped) = handler_lock.lock().await.queue().modify_queue(|q| {
+        if index == 0 || index >= q.len() {
+            return Err(Error::QueuePositionOutOfRange(index));
+        }
+
+        Ok((q.front().map(|queued| queued.handle()), q.drain(1..index).collect::<Vec<_>>()))
+    })?;
+    for track in skipped {
+        track.stop().ok();
+    }
+
+    // Like `skip`, the playing track fades out and the queue moves on once it has ended.
+    if let Some(current) = current {
+        fade::stop(&current, settings::get(ctx, guild_id).await.fade_out()).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use std::{borrow::Cow, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::{AttachmentType, Message},
+};
+
+use crate::framework::error::Error;
+
+use super::limits;
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::request::Request;
+use super::source::{self, Source};
+use super::ytdl;
+use super::{connect, player};
+
+const MAX_IMPORT_TRACKS: usize = 100;
+const MAX_REPORTED_FAILURES: usize = 10;
+
+/// A queue file format other players understand too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QueueFormat {
+    M3u8,
+    Xspf,
+    Json,
+}
+
+impl QueueFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().trim_start_matches('.') {
+            "m3u" | "m3u8" => Some(QueueFormat::M3u8),
+            "xspf" => Some(QueueFormat::Xspf),
+            "json" => Some(QueueFormat::Json),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            QueueFormat::M3u8 => "m3u8",
+            QueueFormat::Xspf => "xspf",
+            QueueFormat::Json => "json",
+        }
+    }
+}
+
+/// One track of an exported queue; the JSON format stores it as is.
+///
+/// Local files have no `source`, their path on the bot's host is nobody else's business;
+/// importing them again falls back to searching for the title.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct QueueFileTrack {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<Source>,
+    title: Option<String>,
+    duration: Option<Duration>,
+}
+
+impl QueueFileTrack {
+    fn location(&self) -> Option<&str> {
+        self.source.as_ref().map(Source::location)
+    }
+}
+
+fn write_m3u8(tracks: &[QueueFileTrack]) -> String {
+    let mut playlist = "#EXTM3U\n".to_string();
+
+    for track in tracks {
+        let seconds = track.duration.map_or(-1, |duration| duration.as_secs() as i64);
+        let title = track.title.as_deref().unwrap_or_default().replace('\n', " ");
+        // Every entry needs a location line, the title stands in for a local file's path.
+        let location = track.location().unwrap_or(&title);
+        playlist.push_str(&format!("#EXTINF:{},{}\n{}\n", seconds, title, location));
+    }
+
+    playlist
+}
+
+fn write_xspf(tracks: &[QueueFileTrack]) -> String {
+    let mut playlist = concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+        "  <trackList>\n",
+    )
+    .to_string();
+
+    for track in tracks {
+        playlist.push_str("    <track>\n");
+        if let Some(location) = track.location() {
+            playlist.push_str(&format!(
+                "      <location>{}</location>\n",
+                html_escape::encode_text(location)
+            ));
+        }
+        if let Some(title) = &track.title {
+            playlist.push_str(&format!("      <title>{}</title>\n", html_escape::encode_text(title)));
+        }
+        if let Some(duration) = track.duration {
+            playlist.push_str(&format!("      <duration>{}</duration>\n", duration.as_millis()));
+        }
+        playlist.push_str("    </track>\n");
+    }
+
+    playlist.push_str("  </trackList>\n</playlist>\n");
+    playlist
+}
+
+/// An entry read from an imported file; `label` says where it was, for reporting failures.
+struct ImportEntry {
+    label: String,
+    location: Option<String>,
+    title: Option<String>,
+    source: Option<Source>,
+}
+
+fn read_m3u(text: &str) -> Vec<ImportEntry> {
+    let mut entries = Vec::new();
+    let mut title = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            title = info
+                .split_once(',')
+                .map(|(_, title)| title.trim().to_string())
+                .filter(|title| !title.is_empty());
+        } else if !line.is_empty() && !line.starts_with('#') {
+            entries.push(ImportEntry {
+                label: format!("line {}", index + 1),
+                location: Some(line.to_string()),
+                title: title.take(),
+                source: None,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Pulls the text out of the first `<tag>` element in `xml`.
+fn xml_element(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+
+    Some(html_escape::decode_html_entities(xml[start..end].trim()).into_owned())
+}
+
+fn read_xspf(text: &str) -> Vec<ImportEntry> {
+    text.split("<track>")
+        .skip(1)
+        .enumerate()
+        .map(|(index, track)| {
+            let track = track.split("</track>").next().unwrap_or_default();
+            ImportEntry {
+                label: format!("track {}", index + 1),
+                location: xml_element(track, "location"),
+                title: xml_element(track, "title"),
+                source: None,
+            }
+        })
+        .collect()
+}
+
+fn read_json(text: &str) -> Result<Vec<ImportEntry>, Error> {
+    let tracks: Vec<serde_json::Value> = serde_json::from_str(text).map_err(|_| {
+        Error::DetailedInvalidArguments("The JSON file must hold a list of tracks".into())
+    })?;
+
+    Ok(tracks
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let track = serde_json::from_value::<QueueFileTrack>(value).ok();
+            ImportEntry {
+                label: format!("entry {}", index + 1),
+                location: track
+                    .as_ref()
+                    .and_then(|track| track.location())
+                    .map(str::to_string),
+                title: track.as_ref().and_then(|track| track.title.clone()),
+                source: track.and_then(|track| track.source),
+            }
+        })
+        .collect())
+}
+
+/// Resolves an imported entry the way `queue` resolves its argument: URLs play as they
+/// are, anything else is searched for by its title.
+///
+/// Only web locations are accepted, an imported file must not open files on the bot's host.
+async fn resolve(entry: &ImportEntry) -> Option<Source> {
+    match (&entry.source, &entry.location) {
+        (Some(source), Some(location)) if source::is_web_url(location) => return Some(source.clone()),
+        (None, Some(location)) if source::is_web_url(location) => {
+            return Some(Source::Ytdl {
+                url: location.clone(),
+            })
+        }
+        _ => {}
+    }
+
+    let title = entry.title.as_deref()?;
+    let found = ytdl::search(title, 1).await.ok()?;
+
+    found.into_iter().next().map(|entry| Source::Ytdl { url: entry.url })
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+async fn export(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let format = match args.single::<String>() {
+        Ok(format) => QueueFormat::parse(&format).ok_or_else(|| {
+            Error::DetailedInvalidArguments("Format must be one of m3u8, xspf or json".into())
+        })?,
+        Err(_) => QueueFormat::M3u8,
+    };
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let queue = handler_lock.lock().await.queue().current_queue();
+    if queue.is_empty() {
+        Err(Error::NothingPlaying)?;
+    }
+
+    let mut tracks = Vec::new();
+    for track in queue {
+        if let Some(source) = source::of(&track).await {
+            let metadata = track.metadata();
+            tracks.push(QueueFileTrack {
+                source: Some(source).filter(|source| source::is_web_url(source.location())),
+                title: metadata.title.clone(),
+                duration: metadata.duration,
+            });
+        }
+    }
+
+    let contents = match format {
+        QueueFormat::M3u8 => write_m3u8(&tracks),
+        QueueFormat::Xspf => write_xspf(&tracks),
+        QueueFormat::Json => serde_json::to_string_pretty(&tracks).map_err(|e| Error::Other(e.into()))?,
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(format!("Exported {} tracks", tracks.len()))
+                .reference_message(msg)
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(contents.into_bytes()),
+                    filename: format!("queue.{}", format.extension()),
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+async fn import(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    // Like `queue`, take the file from the message or from the one it replies to.
+    let attachments = match &msg.referenced_message {
+        Some(referenced) if msg.attachments.is_empty() => &referenced.attachments,
+        _ => &msg.attachments,
+    };
+    let usage = || Error::DetailedInvalidArguments("An attached m3u8, xspf or json file".into());
+    let attachment = attachments.first().ok_or_else(usage)?;
+    let format = attachment
+        .filename
+        .rsplit_once('.')
+        .and_then(|(_, extension)| QueueFormat::parse(extension))
+        .ok_or_else(usage)?;
+
+    let bytes = attachment
+        .download()
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    let entries = match format {
+        QueueFormat::M3u8 => read_m3u(&text),
+        QueueFormat::Xspf => read_xspf(&text),
+        QueueFormat::Json => read_json(&text)?,
+    };
+    if entries.len() > MAX_IMPORT_TRACKS {
+        Err(Error::DetailedInvalidArguments(format!(
+            "Imported files may hold at most {} tracks",
+            MAX_IMPORT_TRACKS
+        )))?;
+    }
+
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+
+    let mut queued = 0;
+    let mut failures = Vec::new();
+    let mut rejections = limits::Rejections::default();
+    for entry in entries.iter() {
+        let enqueued = match resolve(entry).await {
+            Some(source) => player::enqueue_requested(ctx, guild_id, &handler_lock, source, Request::from_message(msg))
+                .await
+                .map_err(|e| rejections.record(&e)),
+            None => Err(false),
+        };
+
+        match enqueued {
+            Ok(_) => queued += 1,
+            // Turned away by a limit, which is reported below instead.
+            Err(true) => {}
+            Err(false) => failures.push(entry.label.clone()),
+        }
+    }
+
+    if queued > 0 {
+        limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+    }
+
+    let mut reply = format!("Imported {} of {} tracks", queued, entries.len());
+    if !failures.is_empty() {
+        reply.push_str(&format!(
+            ", couldn't read {}",
+            failures
+                .iter()
+                .take(MAX_REPORTED_FAILURES)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        if failures.len() > MAX_REPORTED_FAILURES {
+            reply.push_str(&format!(" and {} more", failures.len() - MAX_REPORTED_FAILURES));
+        }
+    }
+    if rejections.count() > 0 {
+        reply.push_str(&format!(", {} turned away: {}", rejections.count(), rejections));
+    }
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
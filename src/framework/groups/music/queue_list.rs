@@ -0,0 +1,221 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::GuildId,
+    },
+};
+use songbird::{
+    tracks::{PlayMode, TrackHandle},
+    typemap::TypeMapKey,
+};
+use tokio::sync::RwLock;
+
+use crate::framework::emoji;
+use crate::framework::error::Error;
+
+use super::timestamp::format_duration;
+use super::request;
+use super::session::{self, LoopMode};
+
+const TRACKS_PER_PAGE: usize = 10;
+const PAGINATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub struct QueuePages;
+
+impl TypeMapKey for QueuePages {
+    type Value = Arc<RwLock<HashMap<u64, QueuePage>>>;
+}
+
+#[derive(Clone, Copy)]
+pub struct QueuePage {
+    guild_id: GuildId,
+    page: usize,
+}
+
+fn page_count(track_count: usize) -> usize {
+    track_count.div_ceil(TRACKS_PER_PAGE).max(1)
+}
+
+async fn queue_embed(tracks: &[TrackHandle], page: usize, loop_mode: LoopMode) -> CreateEmbed {
+    let mut remaining = Duration::ZERO;
+    let mut lines = Vec::with_capacity(TRACKS_PER_PAGE);
+
+    for (index, track) in tracks.iter().enumerate() {
+        let metadata = track.metadata();
+        let duration = metadata.duration.unwrap_or_default();
+
+        let state = match index {
+            0 => track.get_info().await.ok(),
+            _ => None,
+        };
+
+        remaining += match state {
+            Some(state) => duration.saturating_sub(state.position),
+            None => duration,
+        };
+
+        if index / TRACKS_PER_PAGE != page {
+            continue;
+        }
+
+        let title = metadata.title.clone().unwrap_or_else(|| "Unknown".to_string());
+        let title = match &metadata.source_url {
+            Some(url) => format!("[{}]({})", title, url),
+            None => title,
+        };
+        let duration = metadata
+            .duration
+            .map(format_duration)
+            .unwrap_or_else(|| "LIVE".to_string());
+        let requester = match request::of(track).await {
+            Some(request) => format!(" · <@{}>", request.user_id),
+            None => String::new(),
+        };
+
+        lines.push(match index {
+            0 => format!(
+                "**{}** {} `{}`{}",
+                match state {
+                    Some(state) if state.playing == PlayMode::Pause => "Paused",
+                    _ => "Now playing",
+                },
+                title,
+                duration,
+                requester
+            ),
+            _ => format!("`{}.` {} `{}`{}", index, title, duration, requester),
+        });
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("Queue")
+        .description(if lines.is_empty() {
+            "The queue is empty.".to_string()
+        } else {
+            lines.join("\n")
+        })
+        .footer(|f| {
+            f.text(format!(
+                "Page {}/{} · {} tracks · {} remaining · Loop: {}",
+                page + 1,
+                page_count(tracks.len()),
+                tracks.len(),
+                format_duration(remaining),
+                loop_mode,
+            ))
+        });
+
+    embed
+}
+
+pub async fn turn_page(ctx: &Context, reaction: &Reaction) {
+    if reaction.user_id == Some(ctx.cache.current_user_id()) {
+        return;
+    }
+
+    let step: isize = match &reaction.emoji {
+        ReactionType::Unicode(e) if e == emoji::PREVIOUS => -1,
+        ReactionType::Unicode(e) if e == emoji::NEXT => 1,
+        _ => return,
+    };
+
+    let pages_lock = match ctx.data.read().await.get::<QueuePages>() {
+        Some(pages) => pages.clone(),
+        None => return,
+    };
+    let mut queue_page = match pages_lock.read().await.get(&reaction.message_id.0) {
+        Some(queue_page) => *queue_page,
+        None => return,
+    };
+
+    let manager = match songbird::get(ctx).await {
+        Some(manager) => manager,
+        None => return,
+    };
+    let tracks = match manager.get(queue_page.guild_id) {
+        Some(handler_lock) => handler_lock.lock().await.queue().current_queue(),
+        None => Vec::new(),
+    };
+
+    let last_page = page_count(tracks.len()) - 1;
+    queue_page.page = (queue_page.page as isize + step).clamp(0, last_page as isize) as usize;
+
+    match pages_lock.write().await.get_mut(&reaction.message_id.0) {
+        Some(page) => *page = queue_page,
+        None => return,
+    }
+
+    let loop_mode = session::get(ctx, queue_page.guild_id).await.loop_mode;
+    let embed = queue_embed(&tracks, queue_page.page, loop_mode).await;
+
+    reaction
+        .channel_id
+        .edit_message(&ctx.http, reaction.message_id, |m| m.set_embed(embed))
+        .await
+        .ok();
+    reaction.delete(&ctx.http).await.ok();
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("ls")]
+async fn list(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+    let tracks = handler_lock.lock().await.queue().current_queue();
+
+    let loop_mode = session::get(ctx, guild_id).await.loop_mode;
+    let embed = queue_embed(&tracks, 0, loop_mode).await;
+    let paginated = page_count(tracks.len()) > 1;
+
+    let message = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.set_embed(embed);
+            if paginated {
+                m.reactions([
+                    ReactionType::Unicode(emoji::PREVIOUS.to_string()),
+                    ReactionType::Unicode(emoji::NEXT.to_string()),
+                ]);
+            }
+            m
+        })
+        .await?;
+
+    if !paginated {
+        return Ok(());
+    }
+
+    let pages_lock = ctx
+        .data
+        .read()
+        .await
+        .get::<QueuePages>()
+        .ok_or(Error::Unknown)?
+        .clone();
+    pages_lock
+        .write()
+        .await
+        .insert(message.id.0, QueuePage { guild_id, page: 0 });
+
+    let http = ctx.http.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(PAGINATION_TIMEOUT).await;
+
+        pages_lock.write().await.remove(&message.id.0);
+        message.delete_reactions(&http).await.ok();
+    });
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use hyper::{body::HttpBody, Body, Client, Request as HttpRequest};
+use serenity::{
+    async_trait,
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::Message,
+};
+use songbird::{
+    input::Metadata,
+    tracks::TrackHandle,
+    typemap::TypeMapKey,
+    Event, EventContext, EventHandler as VoiceEventHandler,
+};
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::limits;
+use super::request::Request;
+use super::settings;
+use super::source::Source;
+use super::{connect, player};
+
+/// How long a stream plays before we start listening for its song titles.
+pub const ICY_WATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// The song currently announced by a stream's ICY metadata.
+struct StreamTitle;
+
+impl TypeMapKey for StreamTitle {
+    type Value = String;
+}
+
+/// Extracts `StreamTitle` out of a metadata block like `StreamTitle='Artist - Song';StreamUrl='';`.
+fn parse_stream_title(block: &[u8]) -> Option<String> {
+    let block = String::from_utf8_lossy(block);
+    let start = block.find("StreamTitle='")? + "StreamTitle='".len();
+    let end = block[start..].find("';").map_or(block.len(), |end| start + end);
+
+    let title = block[start..end].trim_matches(char::from(0)).trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Opens a second connection to the stream asking for interleaved ICY metadata and
+/// keeps the track's [`StreamTitle`] current until the track ends.
+async fn watch_icy(url: String, track: TrackHandle) -> anyhow::Result<()> {
+    let client = Client::builder().build::<_, Body>(hyper_rustls::HttpsConnector::with_native_roots());
+    let request = HttpRequest::get(url.as_str())
+        .header("Icy-MetaData", "1")
+        .body(Body::empty())?;
+    let mut response = client.request(request).await?;
+
+    // Servers without ICY support simply omit the interval and have nothing to tell us.
+    let metaint = match response
+        .headers()
+        .get("icy-metaint")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+    {
+        Some(metaint) if metaint > 0 => metaint,
+        _ => return Ok(()),
+    };
+
+    let body = response.body_mut();
+    let mut audio_left = metaint;
+    let mut block: Option<(usize, Vec<u8>)> = None;
+
+    while let Some(chunk) = body.data().await {
+        if track.get_info().await.map_or(true, |state| state.playing.is_done()) {
+            break;
+        }
+
+        let mut chunk = &chunk?[..];
+        while !chunk.is_empty() {
+            match &mut block {
+                None if audio_left > 0 => {
+                    let skipped = audio_left.min(chunk.len());
+                    audio_left -= skipped;
+                    chunk = &chunk[skipped..];
+                }
+                None => {
+                    block = Some((chunk[0] as usize * 16, Vec::new()));
+                    chunk = &chunk[1..];
+                }
+                Some((length, bytes)) => {
+                    let taken = (*length - bytes.len()).min(chunk.len());
+                    bytes.extend_from_slice(&chunk[..taken]);
+                    chunk = &chunk[taken..];
+                }
+            }
+
+            if let Some((length, bytes)) = &block {
+                if bytes.len() == *length {
+                    if let Some(title) = parse_stream_title(bytes) {
+                        track.typemap().write().await.insert::<StreamTitle>(title);
+                    }
+                    block = None;
+                    audio_left = metaint;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts [`watch_icy`] once a stream actually begins playing.
+pub struct IcyWatcher {
+    pub url: String,
+}
+
+#[async_trait]
+impl VoiceEventHandler for IcyWatcher {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(track_list) = ctx {
+            let (url, track) = (self.url.clone(), track_list.first()?.1.clone());
+            tokio::spawn(async move {
+                watch_icy(url, track).await.ok();
+            });
+        }
+
+        None
+    }
+}
+
+/// Returns the track's metadata, titled with the song the stream currently announces.
+pub async fn metadata(track: &TrackHandle) -> Metadata {
+    let mut metadata = track.metadata().clone();
+
+    if let Some(title) = track.typemap().read().await.get::<StreamTitle>() {
+        metadata.channel = metadata.title.take();
+        metadata.title = Some(title.clone());
+    }
+
+    metadata
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[min_args(1)]
+#[sub_commands(presets, save, delete)]
+async fn radio(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let target = args.rest().trim();
+
+    let presets = settings::get(ctx, guild_id).await.radio_presets;
+    let (url, name) = match presets.get(&target.to_lowercase()) {
+        Some(url) => (url.clone(), target.to_string()),
+        None if target.starts_with("http") => (target.to_string(), target.to_string()),
+        None => Err(Error::DetailedInvalidArguments(
+            "A stream URL or the name of a radio preset".into(),
+        ))?,
+    };
+
+    limits::check_cooldown(ctx, guild_id, msg.author.id).await?;
+    let handler_lock = connect(ctx, msg).await?;
+    player::enqueue_requested(
+        ctx,
+        guild_id,
+        &handler_lock,
+        Source::Stream { url, name: name.clone() },
+        Request::from_message(msg),
+    )
+    .await?;
+    limits::start_cooldown(ctx, guild_id, msg.author.id).await?;
+
+    msg.reply(&ctx.http, format!("Queued radio {}", name)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases("list", "ls")]
+async fn presets(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let presets = settings::get(ctx, guild_id).await.radio_presets;
+
+    let reply = if presets.is_empty() {
+        "No radio presets saved".to_string()
+    } else {
+        presets
+            .iter()
+            .map(|(name, url)| format!("`{}` <{}>", name, url))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[num_args(2)]
+#[required_permissions("MANAGE_GUILD")]
+async fn save(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    let name = args.single::<String>()?.to_lowercase();
+    let url = args.single::<String>()?;
+    if !url.starts_with("http") {
+        Err(Error::DetailedInvalidArguments("Preset name and stream URL".into()))?;
+    }
+
+    settings::update(ctx, guild_id, |s| {
+        s.radio_presets.insert(name.clone(), url);
+    })
+    .await?;
+
+    msg.reply(&ctx.http, format!("Saved radio preset `{}`", name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[num_args(1)]
+#[aliases("rm")]
+#[required_permissions("MANAGE_GUILD")]
+async fn delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let name = args.single::<String>()?.to_lowercase();
+
+    if !settings::get(ctx, guild_id).await.radio_presets.contains_key(&name) {
+        Err(Error::DetailedInvalidArguments(format!("No radio preset named `{}`", name)))?;
+    }
+
+    settings::update(ctx, guild_id, |s| {
+        s.radio_presets.remove(&name);
+    })
+    .await?;
+
+    msg.reply(&ctx.http, format!("Deleted radio preset `{}`", name))
+        .await?;
+
+    Ok(())
+}
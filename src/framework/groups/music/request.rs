@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::{
+    channel::Message,
+    id::{ChannelId, UserId},
+    Timestamp,
+};
+use songbird::{tracks::TrackHandle, typemap::TypeMapKey};
+
+/// Who asked for a track, stored in the typemap of every queued [`TrackHandle`].
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Request {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub requested_at: Timestamp,
+}
+
+impl Request {
+    pub fn from_message(msg: &Message) -> Self {
+        Self {
+            user_id: msg.author.id,
+            channel_id: msg.channel_id,
+            requested_at: Timestamp::now(),
+        }
+    }
+}
+
+impl TypeMapKey for Request {
+    type Value = Request;
+}
+
+pub async fn attach(track: &TrackHandle, request: Request) {
+    track.typemap().write().await.insert::<Request>(request);
+}
+
+pub async fn of(track: &TrackHandle) -> Option<Request> {
+    track.typemap().read().await.get::<Request>().copied()
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use serenity::{
+    client::Context,
+    model::channel::{Message, ReactionType},
+};
+
+use crate::framework::emoji;
+use crate::framework::error::Error;
+
+use super::timestamp::format_duration;
+use super::ytdl::{self, Entry};
+
+const SEARCH_RESULTS: usize = 5;
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn result_line(index: usize, entry: &Entry) -> String {
+    let mut line = format!(
+        "`{}.` [{}]({})",
+        index + 1,
+        entry.title.as_deref().unwrap_or("Unknown"),
+        entry.url
+    );
+
+    if let Some(uploader) = &entry.uploader {
+        line.push_str(&format!(" · {}", uploader));
+    }
+    if let Some(duration) = entry.duration {
+        line.push_str(&format!(" `{}`", format_duration(duration)));
+    }
+
+    line
+}
+
+/// Searches YouTube for `query` and lets the author pick one of the results,
+/// either by replying with its number or by reacting with the matching keycap.
+pub async fn select(ctx: &Context, msg: &Message, query: &str) -> Result<Entry, Error> {
+    let results = ytdl::search(query, SEARCH_RESULTS).await?;
+
+    if results.is_empty() {
+        Err(Error::NoSearchResults)?;
+    }
+
+    let description = results
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| result_line(index, entry))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let numbers = &emoji::NUMBERS[..results.len()];
+    let selection_message = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title(format!("Search results for \"{}\"", query))
+                    .description(description)
+                    .footer(|f| f.text("Reply with a number or react to pick a track."))
+            })
+            .reactions(numbers.iter().map(|n| ReactionType::Unicode(n.to_string())))
+        })
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    let count = results.len();
+    let reply = msg
+        .channel_id
+        .await_reply(ctx)
+        .author_id(msg.author.id)
+        .timeout(SELECTION_TIMEOUT)
+        .filter(move |m| matches!(m.content.trim().parse::<usize>(), Ok(n) if (1..=count).contains(&n)));
+    let reaction = selection_message
+        .await_reaction(ctx)
+        .author_id(msg.author.id)
+        .timeout(SELECTION_TIMEOUT)
+        .filter(move |r| {
+            matches!(&r.emoji, ReactionType::Unicode(e) if emoji::NUMBERS[..count].contains(&e.as_str()))
+        });
+
+    let selected = tokio::select! {
+        Some(reply) = reply => reply.content.trim().parse::<usize>().ok().map(|n| n - 1),
+        Some(reaction) = reaction => match &reaction.as_inner_ref().emoji {
+            ReactionType::Unicode(e) => numbers.iter().position(|n| n == e),
+            _ => None,
+        },
+        else => None,
+    };
+
+    selection_message.delete(&ctx.http).await.ok();
+
+    selected
+        .and_then(|index| results.get(index).cloned())
+        .ok_or(Error::SelectionTimedOut)
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, GuildId, UserId},
+};
+use songbird::typemap::TypeMapKey;
+use tokio::sync::RwLock;
+
+use crate::framework::error::Error;
+
+use super::vote::SkipVote;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopMode {
+    #[default]
+    Off,
+    Track,
+    Queue,
+}
+
+impl FromStr for LoopMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "none" => Ok(LoopMode::Off),
+            "track" | "song" | "one" => Ok(LoopMode::Track),
+            "queue" | "all" => Ok(LoopMode::Queue),
+            _ => Err(Error::DetailedInvalidArguments(
+                "Loop mode must be one of off, track or queue".into(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LoopMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopMode::Off => write!(f, "off"),
+            LoopMode::Track => write!(f, "track"),
+            LoopMode::Queue => write!(f, "queue"),
+        }
+    }
+}
+
+/// Playback state of a guild that only lives as long as the bot process.
+#[derive(Clone, Debug, Default)]
+pub struct GuildSession {
+    pub loop_mode: LoopMode,
+    /// Text channel the bot was summoned from, used for announcements.
+    pub text_channel_id: Option<ChannelId>,
+    /// The running vote to skip the playing track, if anyone asked.
+    pub skip_vote: Option<SkipVote>,
+    /// When members last queued something, for the enqueue cooldown.
+    pub last_requests: HashMap<UserId, Instant>,
+}
+
+pub struct MusicSessions;
+
+impl TypeMapKey for MusicSessions {
+    type Value = Arc<RwLock<HashMap<u64, GuildSession>>>;
+}
+
+pub async fn get(ctx: &Context, guild_id: GuildId) -> GuildSession {
+    match ctx.data.read().await.get::<MusicSessions>() {
+        Some(sessions) => sessions
+            .read()
+            .await
+            .get(&guild_id.0)
+            .cloned()
+            .unwrap_or_default(),
+        None => GuildSession::default(),
+    }
+}
+
+pub async fn update<F>(ctx: &Context, guild_id: GuildId, f: F) -> Result<GuildSession, Error>
+where
+    F: FnOnce(&mut GuildSession),
+{
+    let sessions_lock = ctx
+        .data
+        .read()
+        .await
+        .get::<MusicSessions>()
+        .ok_or(Error::Unknown)?
+        .clone();
+    let mut sessions = sessions_lock.write().await;
+
+    let session = sessions.entry(guild_id.0).or_default();
+    f(session);
+
+    Ok(session.clone())
+}
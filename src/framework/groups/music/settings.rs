@@ -0,0 +1,172 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::ErrorKind,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    model::id::{GuildId, RoleId},
+};
+use songbird::typemap::TypeMapKey;
+use tokio::sync::RwLock;
+
+use crate::framework::error::Error;
+
+use super::filter::AudioFilter;
+
+/// Music preferences of a single guild, kept across reconnects and restarts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Playback volume in percent, `100` being the source's original loudness.
+    pub volume: u16,
+    /// Seconds to stay in voice with an empty queue before leaving, `0` to stay forever.
+    pub idle_timeout: u64,
+    /// Stream URLs of the `radio` command, by lowercase preset name.
+    pub radio_presets: BTreeMap<String, String>,
+    /// ffmpeg audio filters applied to every track, in the order they were added.
+    pub filters: Vec<AudioFilter>,
+    /// Milliseconds over which a track's volume rises when it starts.
+    pub fade_in: u64,
+    /// Milliseconds over which a track's volume falls before it ends, is skipped or stopped.
+    pub fade_out: u64,
+    /// Milliseconds the next track already plays before the current one ends.
+    pub crossfade: u64,
+    /// Whether tracks are brought to the same loudness before the volume is applied.
+    pub normalize: bool,
+    /// Role whose members may change the guild's shared playlists besides guild managers.
+    pub playlist_role: Option<RoleId>,
+    /// Role whose members skip without a vote and may change playback settings besides guild managers.
+    pub dj_role: Option<RoleId>,
+    /// Percent of the listeners who have to vote for a track to be skipped.
+    pub skip_percent: u8,
+    /// Most tracks the queue may hold, `0` for no limit.
+    pub max_queue_length: usize,
+    /// Most tracks a single member may have queued at once, `0` for no limit.
+    pub max_user_tracks: usize,
+    /// Seconds a track may last at most, `0` for no limit.
+    pub max_track_duration: u64,
+    /// Whether a URL may be queued while it already is.
+    pub allow_duplicates: bool,
+    /// Seconds a member has to wait between queueing commands, `0` for no wait.
+    pub enqueue_cooldown: u64,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            volume: 50,
+            idle_timeout: 300,
+            radio_presets: BTreeMap::new(),
+            filters: Vec::new(),
+            fade_in: 0,
+            fade_out: 0,
+            crossfade: 0,
+            normalize: false,
+            playlist_role: None,
+            dj_role: None,
+            skip_percent: 50,
+            max_queue_length: 500,
+            max_user_tracks: 0,
+            max_track_duration: 0,
+            allow_duplicates: true,
+            enqueue_cooldown: 0,
+        }
+    }
+}
+
+impl GuildSettings {
+    pub fn track_volume(&self) -> f32 {
+        self.volume as f32 / 100.0
+    }
+
+    pub fn fade_in(&self) -> Duration {
+        Duration::from_millis(self.fade_in)
+    }
+
+    pub fn fade_out(&self) -> Duration {
+        Duration::from_millis(self.fade_out)
+    }
+
+    pub fn crossfade(&self) -> Duration {
+        Duration::from_millis(self.crossfade)
+    }
+
+    pub fn max_track_duration(&self) -> Duration {
+        Duration::from_secs(self.max_track_duration)
+    }
+
+    pub fn enqueue_cooldown(&self) -> Duration {
+        Duration::from_secs(self.enqueue_cooldown)
+    }
+}
+
+pub struct MusicSettings {
+    path: PathBuf,
+    guilds: HashMap<u64, GuildSettings>,
+}
+
+impl TypeMapKey for MusicSettings {
+    type Value = Arc<RwLock<MusicSettings>>;
+}
+
+impl MusicSettings {
+    pub async fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let guilds = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::default(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self { path, guilds })
+    }
+
+    pub fn guild(&self, guild_id: GuildId) -> GuildSettings {
+        self.guilds.get(&guild_id.0).cloned().unwrap_or_default()
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(&self.guilds)?).await?;
+
+        Ok(())
+    }
+}
+
+pub async fn get(ctx: &Context, guild_id: GuildId) -> GuildSettings {
+    match ctx.data.read().await.get::<MusicSettings>() {
+        Some(settings) => settings.read().await.guild(guild_id),
+        None => GuildSettings::default(),
+    }
+}
+
+pub async fn update<F>(ctx: &Context, guild_id: GuildId, f: F) -> Result<GuildSettings, Error>
+where
+    F: FnOnce(&mut GuildSettings),
+{
+    let settings_lock = ctx
+        .data
+        .read()
+        .await
+        .get::<MusicSettings>()
+        .ok_or(Error::Unknown)?
+        .clone();
+    let mut settings = settings_lock.write().await;
+
+    let guild_settings = settings.guilds.entry(guild_id.0).or_default();
+    f(guild_settings);
+    let guild_settings = guild_settings.clone();
+
+    settings.save().await?;
+
+    Ok(guild_settings)
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::ErrorKind,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    async_trait,
+    client::Context,
+    model::id::{ChannelId, GuildId},
+    prelude::TypeMap,
+};
+use songbird::{
+    serenity::SongbirdKey, typemap::TypeMapKey, Event, EventContext,
+    EventHandler as VoiceEventHandler,
+};
+use tokio::sync::RwLock;
+
+use crate::framework::error::Error;
+
+use super::join_channel;
+use super::player;
+use super::request::{self, Request};
+use super::session::{self, LoopMode, MusicSessions};
+use super::settings::MusicSettings;
+use super::source::{self, Source};
+
+/// How often the position of the playing track is written down.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct QueuedTrack {
+    source: Source,
+    request: Request,
+}
+
+/// Everything needed to pick a guild's playback back up after a restart.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct QueueSnapshot {
+    voice_channel_id: ChannelId,
+    text_channel_id: ChannelId,
+    loop_mode: LoopMode,
+    volume: u16,
+    /// Position within the first track, the one that was playing.
+    position: Duration,
+    tracks: Vec<QueuedTrack>,
+}
+
+pub struct QueueSnapshots {
+    path: PathBuf,
+    guilds: HashMap<u64, QueueSnapshot>,
+    /// Guilds whose queue is being rebuilt and must not be overwritten half way.
+    restoring: HashSet<u64>,
+    /// Set once shutting down, when the queues falling apart must not be written down anymore.
+    closed: bool,
+}
+
+impl TypeMapKey for QueueSnapshots {
+    type Value = Arc<RwLock<QueueSnapshots>>;
+}
+
+impl QueueSnapshots {
+    pub async fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let guilds = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::default(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self {
+            path,
+            guilds,
+            restoring: HashSet::new(),
+            closed: false,
+        })
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(&self.guilds)?).await?;
+
+        Ok(())
+    }
+}
+
+/// Captures the guild's queue as it is right now, `None` when there is nothing worth restoring.
+///
+/// Works on the raw typemap so it can also run on shutdown, where there is no [`Context`].
+async fn capture(data: &RwLock<TypeMap>, guild_id: GuildId) -> Option<QueueSnapshot> {
+    let (manager, settings_lock, sessions_lock) = {
+        let data = data.read().await;
+        (
+            data.get::<SongbirdKey>()?.clone(),
+            data.get::<MusicSettings>()?.clone(),
+            data.get::<MusicSessions>()?.clone(),
+        )
+    };
+
+    let handler_lock = manager.get(guild_id)?;
+    let (voice_channel_id, queue) = {
+        let handler = handler_lock.lock().await;
+        (handler.current_channel()?, handler.queue().current_queue())
+    };
+
+    let mut position = Duration::ZERO;
+    let mut volume = None;
+    let mut tracks = Vec::new();
+    for track in queue {
+        // Tracks which just ended may not have been popped off the queue yet.
+        let state = match track.get_info().await {
+            Ok(state) if !state.playing.is_done() => state,
+            _ => continue,
+        };
+        if let (Some(source), Some(request)) = (source::of(&track).await, request::of(&track).await) {
+            if tracks.is_empty() {
+                position = player::source_position(&track, state.position).await;
+                volume = player::volume_override(&track).await;
+            }
+            tracks.push(QueuedTrack { source, request });
+        }
+    }
+    if tracks.is_empty() {
+        return None;
+    }
+
+    let session = sessions_lock.read().await.get(&guild_id.0).cloned().unwrap_or_default();
+    // Restored tracks keep the volume they were saved with until it's changed.
+    let volume = match volume {
+        Some(volume) => volume,
+        None => settings_lock.read().await.guild(guild_id).volume,
+    };
+
+    Some(QueueSnapshot {
+        voice_channel_id: ChannelId(voice_channel_id.0),
+        text_channel_id: session.text_channel_id?,
+        loop_mode: session.loop_mode,
+        volume,
+        position,
+        tracks,
+    })
+}
+
+async fn save_guilds(
+    data: &RwLock<TypeMap>,
+    guild_ids: Vec<GuildId>,
+    close: bool,
+) -> Result<(), Error> {
+    let snapshots_lock = data
+        .read()
+        .await
+        .get::<QueueSnapshots>()
+        .ok_or(Error::Unknown)?
+        .clone();
+
+    let mut captured = Vec::new();
+    for guild_id in guild_ids {
+        captured.push((guild_id, capture(data, guild_id).await));
+    }
+
+    let mut snapshots = snapshots_lock.write().await;
+    if snapshots.closed {
+        return Ok(());
+    }
+    snapshots.closed = close;
+    for (guild_id, snapshot) in captured {
+        if snapshots.restoring.contains(&guild_id.0) {
+            continue;
+        }
+        match snapshot {
+            Some(snapshot) => snapshots.guilds.insert(guild_id.0, snapshot),
+            None => snapshots.guilds.remove(&guild_id.0),
+        };
+    }
+    snapshots.save().await?;
+
+    Ok(())
+}
+
+/// Writes down the guild's current queue.
+pub async fn save(ctx: &Context, guild_id: GuildId) -> Result<(), Error> {
+    save_guilds(&ctx.data, vec![guild_id], false).await
+}
+
+/// Writes down the queue of every guild with music going for the last time before shutting down.
+pub async fn save_all(data: &RwLock<TypeMap>) -> Result<(), Error> {
+    let guild_ids = match data.read().await.get::<QueueSnapshots>() {
+        Some(snapshots_lock) => snapshots_lock
+            .read()
+            .await
+            .guilds
+            .keys()
+            .map(|guild_id| GuildId(*guild_id))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    save_guilds(data, guild_ids, true).await
+}
+
+/// Drops the guild's snapshot, for when the bot was asked to leave and shouldn't come back.
+pub async fn forget(ctx: &Context, guild_id: GuildId) -> Result<(), Error> {
+    let snapshots_lock = ctx
+        .data
+        .read()
+        .await
+        .get::<QueueSnapshots>()
+        .ok_or(Error::Unknown)?
+        .clone();
+    let mut snapshots = snapshots_lock.write().await;
+
+    if snapshots.guilds.remove(&guild_id.0).is_some() {
+        snapshots.save().await?;
+    }
+
+    Ok(())
+}
+
+async fn restore_guild(ctx: &Context, guild_id: GuildId, snapshot: QueueSnapshot) -> Result<(), Error> {
+    let handler_lock =
+        join_channel(ctx, guild_id, snapshot.voice_channel_id, snapshot.text_channel_id).await?;
+
+    session::update(ctx, guild_id, |s| s.loop_mode = snapshot.loop_mode).await?;
+
+    for (index, queued) in snapshot.tracks.into_iter().enumerate() {
+        let seekable = queued.source.is_seekable();
+        let track = match player::enqueue(ctx, guild_id, &handler_lock, queued.source, queued.request).await {
+            Ok(track) => track,
+            Err(_) => continue,
+        };
+        // The saved volume belongs to these tracks, not to the guild's setting.
+        player::override_volume(&track, Some(snapshot.volume)).await;
+
+        if index == 0 && seekable && !snapshot.position.is_zero() {
+            player::seek(&track, snapshot.position).await.ok();
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejoins every guild that had music going when the bot went down and queues it all up again.
+pub async fn restore(ctx: &Context) {
+    let snapshots_lock = match ctx.data.read().await.get::<QueueSnapshots>() {
+        Some(snapshots_lock) => snapshots_lock.clone(),
+        None => return,
+    };
+    let manager = match songbird::get(ctx).await {
+        Some(manager) => manager,
+        None => return,
+    };
+
+    let snapshots = {
+        let mut snapshots = snapshots_lock.write().await;
+        let guilds = snapshots.guilds.clone();
+        snapshots.restoring.extend(guilds.keys());
+        guilds
+    };
+
+    for (guild_id, snapshot) in snapshots {
+        let guild_id = GuildId(guild_id);
+
+        // `ready` fires again on reconnects, when the music never stopped.
+        let playing = match manager.get(guild_id) {
+            Some(handler_lock) => !handler_lock.lock().await.queue().is_empty(),
+            None => false,
+        };
+
+        let (ctx, snapshots_lock) = (ctx.clone(), snapshots_lock.clone());
+        tokio::spawn(async move {
+            if !playing {
+                if let Err(why) = restore_guild(&ctx, guild_id, snapshot).await {
+                    println!("Err restoring the queue of {}: {:?}", guild_id, why);
+                }
+            }
+
+            snapshots_lock.write().await.restoring.remove(&guild_id.0);
+            save(&ctx, guild_id).await.ok();
+        });
+    }
+}
+
+/// Keeps the guild's snapshot current as tracks end and play on.
+pub struct Snapshotter {
+    pub ctx: Context,
+    pub guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for Snapshotter {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+        save(&self.ctx, self.guild_id).await.ok();
+
+        None
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::{async_trait, model::channel::Attachment};
+use songbird::{
+    input::{
+        children_to_reader,
+        error::{Error as InputError, Result as InputResult},
+        restartable::{Restart, Restartable},
+        Codec, Container, Input, Metadata,
+    },
+    tracks::TrackHandle,
+    typemap::TypeMapKey,
+};
+use tokio::process::Command as TokioCommand;
+
+use crate::framework::error::Error;
+
+use super::ytdl;
+
+const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "ogg", "oga", "opus", "flac", "wav", "m4a", "aac"];
+
+/// Where a track's audio comes from, kept in the typemap of every queued [`TrackHandle`]
+/// so the track can be created again later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Source {
+    /// Anything youtube-dl can resolve, such as a YouTube video page.
+    Ytdl { url: String },
+    /// A media file ffmpeg can open directly, such as a Discord attachment.
+    File { location: String, name: String },
+    /// An endless live stream such as an Icecast radio, played without a timeline.
+    Stream { url: String, name: String },
+}
+
+pub fn is_audio_file_name(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+    })
+}
+
+/// Whether `location` is a web URL rather than a path on the bot's host.
+pub fn is_web_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+impl Source {
+    /// The URL or file location the audio is read from.
+    pub fn location(&self) -> &str {
+        match self {
+            Source::Ytdl { url } | Source::Stream { url, .. } => url,
+            Source::File { location, .. } => location,
+        }
+    }
+
+    pub fn from_attachment(attachment: &Attachment) -> Option<Self> {
+        let is_audio = match &attachment.content_type {
+            Some(content_type) => content_type.starts_with("audio/"),
+            None => is_audio_file_name(&attachment.filename),
+        };
+
+        is_audio.then(|| Source::File {
+            location: attachment.url.clone(),
+            name: attachment.filename.clone(),
+        })
+    }
+
+    /// Creates a lazy restartable input, so that we don't pay for decoding
+    /// tracks which aren't actually live yet.
+    ///
+    /// `filters` is an ffmpeg `-af` filter chain applied every time the source is (re)started.
+    /// Known `metadata`, such as a playlist listing's, spares looking the source up again.
+    pub async fn input(&self, filters: Option<String>, metadata: Option<Metadata>) -> Result<Input, Error> {
+        let restarter = SourceRestarter {
+            source: self.clone(),
+            filters,
+            metadata,
+        };
+
+        let mut input: Input = Restartable::new(restarter, true)
+            .await
+            .map_err(|e| Error::Other(e.into()))?
+            .into();
+
+        // ffprobe gives us the duration, but files rarely carry a useful title tag.
+        // Library files are linked nowhere, their path on the bot's host stays private.
+        if let Source::File { location, name } | Source::Stream { url: location, name } = self {
+            input.metadata.title = Some(name.clone());
+            input.metadata.source_url = Some(location.clone()).filter(|location| is_web_url(location));
+        }
+
+        Ok(input)
+    }
+
+    pub fn is_seekable(&self) -> bool {
+        !matches!(self, Source::Stream { .. })
+    }
+}
+
+/// Spawns the processes decoding a [`Source`], passing the guild's audio filters to ffmpeg.
+///
+/// Live streams are always restarted at their live edge since there is nothing to seek in.
+struct SourceRestarter {
+    source: Source,
+    filters: Option<String>,
+    metadata: Option<Metadata>,
+}
+
+impl SourceRestarter {
+    fn ffmpeg(&self, input: &str, start: Option<Duration>) -> Command {
+        let mut ffmpeg = Command::new("ffmpeg");
+
+        if let Source::Stream { .. } = self.source {
+            ffmpeg.args(["-reconnect", "1", "-reconnect_streamed", "1", "-reconnect_delay_max", "5"]);
+        } else if let Some(start) = start {
+            ffmpeg.args(["-ss", &format!("{:.3}", start.as_secs_f64())]);
+        }
+        ffmpeg.args(["-i", input]);
+        if let Some(filters) = &self.filters {
+            ffmpeg.args(["-af", filters]);
+        }
+        ffmpeg
+            .args(["-f", "s16le", "-ac", "2", "-ar", "48000", "-acodec", "pcm_f32le", "-"])
+            .stderr(Stdio::null())
+            .stdout(Stdio::piped());
+
+        ffmpeg
+    }
+}
+
+#[async_trait]
+impl Restart for SourceRestarter {
+    async fn call_restart(&mut self, time: Option<Duration>) -> InputResult<Input> {
+        let children = match &self.source {
+            Source::Ytdl { url } => {
+                let mut youtube_dl = Command::new(ytdl::YOUTUBE_DL_COMMAND)
+                    .args(["-f", "webm[abr>0]/bestaudio/best", "-R", "infinite", "--no-playlist"])
+                    .args(["--ignore-config", "--no-warnings", "-o", "-", url])
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let stdout = youtube_dl.stdout.take().ok_or(InputError::Stdout)?;
+                let ffmpeg = self.ffmpeg("-", time).stdin(stdout).spawn()?;
+
+                vec![youtube_dl, ffmpeg]
+            }
+            Source::File { location: input, .. } | Source::Stream { url: input, .. } => {
+                vec![self.ffmpeg(input, time).stdin(Stdio::null()).spawn()?]
+            }
+        };
+
+        Ok(Input::new(
+            true,
+            children_to_reader::<f32>(children),
+            Codec::FloatPcm,
+            Container::Raw,
+            self.metadata.clone(),
+        ))
+    }
+
+    async fn lazy_init(&mut self) -> InputResult<(Option<Metadata>, Codec, Container)> {
+        let mut metadata = match (&self.source, self.metadata.take()) {
+            (_, Some(metadata)) => metadata,
+            (Source::Ytdl { url }, None) => ytdl::metadata(url).await?,
+            (Source::File { location, .. }, None) => {
+                let output = TokioCommand::new("ffprobe")
+                    .args(["-v", "quiet", "-of", "json", "-show_format", "-show_streams", "-i"])
+                    .arg(location)
+                    .stdin(Stdio::null())
+                    .output()
+                    .await?;
+                let value = serde_json::from_slice(&output.stdout).map_err(|_| InputError::Metadata)?;
+
+                Metadata::from_ffprobe_json(&value)
+            }
+            (Source::Stream { .. }, None) => Metadata::default(),
+        };
+
+        // Whatever the source, ffmpeg hands us 48kHz stereo.
+        metadata.channels = Some(2);
+        metadata.sample_rate = Some(48000);
+        self.metadata = Some(metadata.clone());
+
+        Ok((Some(metadata), Codec::FloatPcm, Container::Raw))
+    }
+}
+
+impl TypeMapKey for Source {
+    type Value = Source;
+}
+
+pub async fn attach(track: &TrackHandle, source: Source) {
+    track.typemap().write().await.insert::<Source>(source);
+}
+
+pub async fn of(track: &TrackHandle) -> Option<Source> {
+    track.typemap().read().await.get::<Source>().cloned()
+}
@@ -0,0 +1,139 @@
+use std::{str::FromStr, time::Duration};
+
+use crate::framework::error::Error;
+
+const ACCEPTED_FORMATS: &str =
+    "Use a position like 90, 90s, 1m30s, 1:30 or 1:02:03, or +30 / -15 to seek relatively";
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Parses `1:23`, `1:02:03`, `90`, `90s` and unit combinations such as `1h2m3s`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    if s.contains(':') {
+        let parts = s.split(':').collect::<Vec<_>>();
+        if parts.len() > 3 {
+            return None;
+        }
+
+        return parts
+            .iter()
+            .try_fold(0u64, |total, part| {
+                total.checked_mul(60)?.checked_add(part.parse::<u64>().ok()?)
+            })
+            .map(Duration::from_secs);
+    }
+
+    if let Ok(seconds) = s.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in s.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' if !number.is_empty() => {
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = total.checked_add(number.parse::<u64>().ok()?.checked_mul(unit)?)?;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+
+    if number.is_empty() && !s.is_empty() {
+        Some(Duration::from_secs(total))
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SeekTarget {
+    Absolute(Duration),
+    Forward(Duration),
+    Backward(Duration),
+}
+
+impl SeekTarget {
+    pub fn resolve(self, position: Duration) -> Duration {
+        match self {
+            SeekTarget::Absolute(target) => target,
+            SeekTarget::Forward(offset) => position.saturating_add(offset),
+            SeekTarget::Backward(offset) => position.saturating_sub(offset),
+        }
+    }
+}
+
+impl FromStr for SeekTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let target = match s.trim() {
+            s if s.starts_with('+') => parse_duration(&s[1..]).map(SeekTarget::Forward),
+            s if s.starts_with('-') => parse_duration(&s[1..]).map(SeekTarget::Backward),
+            s => parse_duration(s).map(SeekTarget::Absolute),
+        };
+
+        target.ok_or_else(|| Error::DetailedInvalidArguments(ACCEPTED_FORMATS.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Option<Duration> {
+        Some(Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn parses_clock_positions() {
+        assert_eq!(parse_duration("1:23"), secs(83));
+        assert_eq!(parse_duration("1:02:03"), secs(3723));
+        assert_eq!(parse_duration("1:"), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_duration("90"), secs(90));
+        assert_eq!(parse_duration("90s"), secs(90));
+        assert_eq!(parse_duration("1h2m3s"), secs(3723));
+        assert_eq!(parse_duration("1h2"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn rejects_overflowing_input() {
+        assert_eq!(parse_duration("18446744073709551615:0"), None);
+        assert_eq!(parse_duration("99999999999999999999"), None);
+        assert_eq!(parse_duration("18446744073709551615h"), None);
+        assert_eq!(parse_duration("18446744073709551615s1s"), None);
+    }
+
+    #[test]
+    fn resolves_relative_seeks() {
+        let position = Duration::from_secs(60);
+        let resolve = |s: &str| s.parse::<SeekTarget>().ok().map(|target| target.resolve(position));
+
+        assert_eq!(resolve("+30"), secs(90));
+        assert_eq!(resolve("-15"), secs(45));
+        assert_eq!(resolve("-90"), secs(0));
+        assert_eq!(resolve("1:30"), secs(90));
+        assert_eq!(resolve("+18446744073709551615"), Some(Duration::MAX));
+    }
+}
@@ -0,0 +1,62 @@
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::channel::Message,
+};
+
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::loudness;
+use super::player;
+use super::settings;
+use super::snapshot;
+use super::vote;
+
+const MAX_VOLUME: u16 = 200;
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+#[max_args(1)]
+#[aliases("v", "vol")]
+async fn volume(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if args.is_empty() {
+        let guild_settings = settings::get(ctx, guild_id).await;
+        msg.reply(&ctx.http, format!("Volume is {}%", guild_settings.volume))
+            .await?;
+
+        return Ok(());
+    }
+
+    vote::require_dj(ctx, guild_id, msg.author.id).await?;
+    let volume = args
+        .single::<u16>()
+        .ok()
+        .filter(|volume| *volume <= MAX_VOLUME)
+        .ok_or_else(|| {
+            Error::DetailedInvalidArguments(format!("Volume must be between 0 and {}", MAX_VOLUME))
+        })?;
+
+    let guild_settings = settings::update(ctx, guild_id, |s| s.volume = volume).await?;
+
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+
+    // Tracks already waiting in the queue were created with the old volume.
+    if let Some(handler_lock) = manager.get(guild_id) {
+        for track_handle in handler_lock.lock().await.queue().current_queue() {
+            player::override_volume(&track_handle, None).await;
+            track_handle
+                .set_volume(guild_settings.track_volume() * loudness::gain(&track_handle).await)
+                .map_err(|e| Error::Other(e.into()))?;
+        }
+    }
+    snapshot::save(ctx, guild_id).await?;
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+
+use serenity::{
+    client::Context,
+    framework::standard::{Args, CommandResult, macros::command},
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::{GuildId, MessageId, RoleId, UserId},
+    },
+};
+use songbird::tracks::TrackHandle;
+
+use crate::framework::emoji;
+use crate::framework::error::Error;
+
+use super::checks::SAMEVOICECHANNEL_CHECK;
+use super::fade;
+use super::idle;
+use super::request;
+use super::session;
+use super::settings;
+
+/// Votes to skip one particular track; votes for any other track are void.
+#[derive(Clone, Debug)]
+pub struct SkipVote {
+    track: TrackHandle,
+    voters: HashSet<UserId>,
+    message_id: Option<MessageId>,
+}
+
+/// Whether the member holds the guild's DJ role or may manage the guild anyway.
+///
+/// The one rule for everything DJs may do; without a DJ role only guild managers are DJs.
+pub async fn is_dj(ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+    let member = match guild_id.member(ctx, user_id).await {
+        Ok(member) => member,
+        Err(_) => return false,
+    };
+
+    let dj_role = settings::get(ctx, guild_id).await.dj_role;
+    if dj_role.is_some_and(|role_id| member.roles.contains(&role_id)) {
+        return true;
+    }
+
+    member
+        .permissions(&ctx.cache)
+        .is_ok_and(|permissions| permissions.manage_guild())
+}
+
+/// Fails unless the member is a DJ, for commands changing the guild's playback settings.
+pub async fn require_dj(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<(), Error> {
+    if !is_dj(ctx, guild_id, user_id).await {
+        Err(Error::NotDj)?;
+    }
+
+    Ok(())
+}
+
+/// Listeners needed to skip, never fewer than one.
+fn required_votes(listeners: usize, percent: u8) -> usize {
+    (listeners * percent as usize).div_ceil(100).max(1)
+}
+
+async fn skip_now(ctx: &Context, guild_id: GuildId, track: &TrackHandle) -> Result<(), Error> {
+    session::update(ctx, guild_id, |s| s.skip_vote = None).await?;
+
+    fade::stop(track, settings::get(ctx, guild_id).await.fade_out()).await
+}
+
+/// Whether the user may skip the track without asking anyone.
+async fn skips_instantly(ctx: &Context, guild_id: GuildId, track: &TrackHandle, user_id: UserId) -> bool {
+    let requester = request::of(track).await.map(|request| request.user_id);
+
+    requester == Some(user_id) || is_dj(ctx, guild_id, user_id).await
+}
+
+/// Counts `user_id`'s vote against the playing track and returns the votes
+/// cast and needed, skipping the track right away once enough agree.
+async fn cast(
+    ctx: &Context,
+    guild_id: GuildId,
+    track: &TrackHandle,
+    user_id: UserId,
+) -> Result<(usize, usize), Error> {
+    let (_, listeners) = idle::listeners(ctx, guild_id).ok_or(Error::NotInVoiceChannel)?;
+    if !listeners.contains(&user_id) {
+        Err(Error::NotInVoiceChannel)?;
+    }
+
+    let percent = settings::get(ctx, guild_id).await.skip_percent;
+    let required = required_votes(listeners.len(), percent);
+
+    let vote = session::update(ctx, guild_id, |s| {
+        let mut vote = match s.skip_vote.take() {
+            Some(vote) if vote.track.uuid() == track.uuid() => vote,
+            _ => SkipVote {
+                track: track.clone(),
+                voters: HashSet::new(),
+                message_id: None,
+            },
+        };
+        vote.voters.insert(user_id);
+        s.skip_vote = Some(vote);
+    })
+    .await?
+    .skip_vote
+    .ok_or(Error::Unknown)?;
+
+    // Votes of listeners who left since don't count anymore.
+    let votes = vote.voters.iter().filter(|voter| listeners.contains(voter)).count();
+    if votes >= required {
+        skip_now(ctx, guild_id, track).await?;
+    }
+
+    Ok((votes, required))
+}
+
+async fn current_track(ctx: &Context, guild_id: GuildId) -> Result<Option<TrackHandle>, Error> {
+    let manager = songbird::get(ctx)
+        .await
+        .ok_or(Error::SongbirdInitialization)?
+        .clone();
+    let handler_lock = manager.get(guild_id).ok_or(Error::NotInVoiceChannel)?;
+
+    let current = handler_lock.lock().await.queue().current();
+    Ok(current)
+}
+
+#[command]
+#[only_in(guilds)]
+#[checks(SameVoiceChannel)]
+async fn skip(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+    let current = match current_track(ctx, guild_id).await? {
+        Some(current) => current,
+        None => return Ok(()),
+    };
+
+    // Whoever asked for the track may take it back, and DJs don't need to ask around.
+    if skips_instantly(ctx, guild_id, &current, msg.author.id).await {
+        skip_now(ctx, guild_id, &current).await?;
+        return Ok(());
+    }
+
+    let (votes, required) = cast(ctx, guild_id, &current, msg.author.id).await?;
+    if votes >= required {
+        msg.reply(&ctx.http, "Skipped by vote").await?;
+        return Ok(());
+    }
+
+    let title = current
+        .metadata()
+        .title
+        .clone()
+        .unwrap_or_else(|| "Unknown".to_string());
+    let vote_message = msg
+        .reply(
+            &ctx.http,
+            format!(
+                "Vote to skip **{}**: {}/{}, react with {} to agree",
+                title,
+                votes,
+                required,
+                emoji::SKIP
+            ),
+        )
+        .await?;
+    vote_message
+        .react(&ctx.http, ReactionType::Unicode(emoji::SKIP.to_string()))
+        .await?;
+
+    session::update(ctx, guild_id, |s| {
+        if let Some(vote) = s.skip_vote.as_mut().filter(|vote| vote.track.uuid() == current.uuid()) {
+            vote.message_id = Some(vote_message.id);
+        }
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Counts ⏭️ reactions on the message of the running vote as votes.
+pub async fn reaction_vote(ctx: &Context, reaction: &Reaction) {
+    let (guild_id, user_id) = match (reaction.guild_id, reaction.user_id) {
+        (Some(guild_id), Some(user_id)) if user_id != ctx.cache.current_user_id() => (guild_id, user_id),
+        _ => return,
+    };
+    if !matches!(&reaction.emoji, ReactionType::Unicode(e) if e == emoji::SKIP) {
+        return;
+    }
+
+    let vote = match session::get(ctx, guild_id).await.skip_vote {
+        Some(vote) if vote.message_id == Some(reaction.message_id) => vote,
+        _ => return,
+    };
+    let current = match current_track(ctx, guild_id).await {
+        Ok(Some(current)) if current.uuid() == vote.track.uuid() => current,
+        _ => return,
+    };
+
+    let skipped = if skips_instantly(ctx, guild_id, &current, user_id).await {
+        skip_now(ctx, guild_id, &current).await.is_ok()
+    } else {
+        cast(ctx, guild_id, &current, user_id)
+            .await
+            .is_ok_and(|(votes, required)| votes >= required)
+    };
+
+    if skipped {
+        reaction
+            .channel_id
+            .say(&ctx.http, "Skipped by vote")
+            .await
+            .ok();
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+#[aliases("skipvotes")]
+async fn voteskip(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if !args.is_empty() {
+        require_dj(ctx, guild_id, msg.author.id).await?;
+
+        let percent = args
+            .single::<u8>()
+            .ok()
+            .filter(|percent| *percent <= 100)
+            .ok_or_else(|| {
+                Error::DetailedInvalidArguments(
+                    "Percent of listeners needed to skip, 0 to let anyone skip".into(),
+                )
+            })?;
+        settings::update(ctx, guild_id, |s| s.skip_percent = percent).await?;
+    }
+
+    let reply = match settings::get(ctx, guild_id).await.skip_percent {
+        0 => "Anyone can skip right away".to_string(),
+        percent => format!("Skipping takes {}% of the listeners", percent),
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(1)]
+#[required_permissions("MANAGE_GUILD")]
+async fn dj(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild(&ctx.cache).ok_or(Error::Unknown)?.id;
+
+    if !args.is_empty() {
+        let dj_role = match args.single::<String>()?.as_str() {
+            "none" | "off" => None,
+            role => Some(role.parse::<RoleId>().map_err(|_| {
+                Error::DetailedInvalidArguments("A role mention or id, or none".into())
+            })?),
+        };
+        settings::update(ctx, guild_id, |s| s.dj_role = dj_role).await?;
+    }
+
+    let reply = match settings::get(ctx, guild_id).await.dj_role {
+        Some(role_id) => format!("The DJ role is <@&{}>", role_id),
+        None => "There is no DJ role, only guild managers are DJs".to_string(),
+    };
+    msg.reply(&ctx.http, reply).await?;
+
+    Ok(())
+}
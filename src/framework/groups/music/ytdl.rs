@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use serde_json::Value;
+use songbird::input::{error::Error as InputError, Metadata};
+use tokio::process::Command;
+
+use crate::framework::error::Error;
+
+pub const YOUTUBE_DL_COMMAND: &str = "youtube-dl";
+
+/// A single video listed by `youtube-dl --flat-playlist`, not yet resolved to a stream.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub url: String,
+    pub title: Option<String>,
+    pub uploader: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl Entry {
+    fn parse(value: &Value) -> Option<Self> {
+        let url = value
+            .get("webpage_url")
+            .or_else(|| value.get("url"))
+            .and_then(Value::as_str)?;
+
+        // Flat YouTube entries only carry the video id in `url`.
+        let url = if url.starts_with("http") {
+            url.to_string()
+        } else if value.get("ie_key").and_then(Value::as_str) == Some("Youtube") {
+            format!("https://www.youtube.com/watch?v={}", url)
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            url,
+            title: value.get("title").and_then(Value::as_str).map(str::to_string),
+            uploader: value
+                .get("uploader")
+                .or_else(|| value.get("channel"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            duration: value
+                .get("duration")
+                .and_then(Value::as_f64)
+                .map(Duration::from_secs_f64),
+        })
+    }
+
+    /// What the listing already tells about the video, enough to queue it without looking it up.
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            title: self.title.clone(),
+            channel: self.uploader.clone(),
+            duration: self.duration,
+            source_url: Some(self.url.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+async fn flat_playlist(target: &str, limit: usize) -> Result<Vec<Value>, Error> {
+    let output = Command::new(YOUTUBE_DL_COMMAND)
+        .args([
+            "--flat-playlist",
+            "-J",
+            "--ignore-config",
+            "--no-warnings",
+            "--playlist-end",
+            &limit.to_string(),
+            target,
+        ])
+        .output()
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    if !output.status.success() {
+        Err(Error::Other(anyhow::anyhow!(
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))?;
+    }
+
+    let value: Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| Error::Other(e.into()))?;
+
+    Ok(match value.get("entries").and_then(Value::as_array) {
+        Some(entries) => entries.clone(),
+        None => vec![value],
+    })
+}
+
+pub async fn search(query: &str, count: usize) -> Result<Vec<Entry>, Error> {
+    let entries = flat_playlist(&format!("ytsearch{}:{}", count, query), count).await?;
+
+    Ok(entries.iter().filter_map(Entry::parse).collect())
+}
+
+/// Lists up to `limit` entries of a playlist; entries that can't be played are `None`.
+pub async fn playlist(url: &str, limit: usize) -> Result<Vec<Option<Entry>>, Error> {
+    let entries = flat_playlist(url, limit).await?;
+
+    Ok(entries.iter().map(Entry::parse).collect())
+}
+
+pub fn is_playlist_url(url: &str) -> bool {
+    ["list=", "/playlist", "/sets/", "/album/"]
+        .iter()
+        .any(|pattern| url.contains(pattern))
+}
+
+/// Fetches the metadata songbird would read while starting `url`, without downloading anything.
+pub async fn metadata(url: &str) -> Result<Metadata, InputError> {
+    let output = Command::new(YOUTUBE_DL_COMMAND)
+        .args(["-j", "--no-playlist", "--ignore-config", "--no-warnings", url])
+        .output()
+        .await?;
+    let value = serde_json::from_slice(&output.stdout).map_err(|_| InputError::Metadata)?;
+
+    Ok(Metadata::from_ytdl_output(value))
+}
+
+/// Resolves `url` to the audio stream songbird would play for it.
+pub async fn stream_url(url: &str) -> Option<String> {
+    let output = Command::new(YOUTUBE_DL_COMMAND)
+        .args(["-f", "webm[abr>0]/bestaudio/best", "-g", "--no-playlist"])
+        .args(["--ignore-config", "--no-warnings", url])
+        .output()
+        .await
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::to_string)
+}
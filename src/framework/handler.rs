@@ -4,6 +4,7 @@ use serenity::{
     model::gateway::Ready,
 };
 use serenity::model::channel::Reaction;
+use serenity::model::voice::VoiceState;
 
 #[derive(Default)]
 pub struct EventHandler {
@@ -24,6 +25,12 @@ impl EventHandlerBase for EventHandler {
         }
     }
 
+    async fn voice_state_update(&self, c: Context, old: Option<VoiceState>, new: VoiceState) {
+        for handler in self.handlers.iter() {
+            handler.voice_state_update(c.clone(), old.clone(), new.clone()).await;
+        }
+    }
+
     async fn ready(&self, c: Context, r: Ready) {
         for handler in self.handlers.iter() {
             handler.ready(c.clone(), r.clone()).await;
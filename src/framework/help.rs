@@ -20,6 +20,7 @@ If you want more information about a specific command, just pass the command as
 #[indention_prefix = "+"]
 #[lacking_permissions = "Hide"]
 #[lacking_role = "Nothing"]
+#[lacking_conditions = "Hide"]
 #[wrong_channel = "Strike"]
 pub async fn help_command(
     context: &Context,
@@ -22,6 +22,7 @@ impl FrameworkBuilder<'_> for ClientBuilder {
             .configure(|c| c.prefix("!"))
             .before(groups::hooks::before)
             .after(groups::hooks::after)
+            .on_dispatch_error(groups::hooks::dispatch_error)
             .help(&help::HELP_COMMAND)
             .group(&groups::general::GENERAL_GROUP)
             .group(&groups::music::MUSIC_GROUP);
@@ -6,17 +6,16 @@ extern crate hyper_rustls;
 #[cfg(feature = "translate")]
 extern crate yup_oauth2 as oauth2;
 
-#[cfg(feature = "translate")]
 use std::collections::HashMap;
 #[cfg(feature = "translate")]
 use std::default::Default;
 use std::env;
-#[cfg(feature = "translate")]
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use serenity::client::ClientBuilder;
 use serenity::prelude::GatewayIntents;
-#[cfg(feature = "translate")]
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::RwLock;
 #[cfg(feature = "translate")]
 use translate3::Translate;
@@ -24,6 +23,15 @@ use translate3::Translate;
 #[cfg(feature = "translate")]
 use framework::groups::translate::{GoogleProjectId, GoogleTranslate, LastTranslationLanguageCache};
 
+use framework::groups::music::history::MusicHistory;
+use framework::groups::music::library::{self, MusicLibrary};
+use framework::groups::music::loudness::LoudnessCache;
+use framework::groups::music::playlist::Playlists;
+use framework::groups::music::queue_list::QueuePages;
+use framework::groups::music::session::MusicSessions;
+use framework::groups::music::settings::MusicSettings;
+use framework::groups::music::snapshot::{self, QueueSnapshots};
+
 use crate::framework::AttachableClientBuilder;
 
 mod framework;
@@ -34,6 +42,7 @@ async fn main() {
     env_logger::init();
 
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    let data_dir = PathBuf::from(env::var("DATA_DIR").unwrap_or_else(|_| "data".to_string()));
 
     let intents = GatewayIntents::GUILDS
         .union(GatewayIntents::GUILD_MESSAGES)
@@ -46,6 +55,39 @@ async fn main() {
         .await
         .expect("Err creating client");
 
+    {
+        let mut data = client.data.write().await;
+
+        let music_settings = MusicSettings::load(data_dir.join("guilds.json"))
+            .await
+            .expect("Err loading music settings");
+
+        data.insert::<QueuePages>(Arc::new(RwLock::new(HashMap::default())));
+        data.insert::<MusicSettings>(Arc::new(RwLock::new(music_settings)));
+        data.insert::<MusicSessions>(Arc::new(RwLock::new(HashMap::default())));
+        data.insert::<MusicHistory>(Arc::new(RwLock::new(HashMap::default())));
+
+        let playlists = Playlists::load(data_dir.join("playlists.json"))
+            .await
+            .expect("Err loading playlists");
+        data.insert::<Playlists>(Arc::new(RwLock::new(playlists)));
+
+        let queue_snapshots = QueueSnapshots::load(data_dir.join("queues.json"))
+            .await
+            .expect("Err loading queue snapshots");
+        data.insert::<QueueSnapshots>(Arc::new(RwLock::new(queue_snapshots)));
+
+        let loudness_cache = LoudnessCache::load(data_dir.join("loudness.json"))
+            .await
+            .expect("Err loading loudness cache");
+        data.insert::<LoudnessCache>(Arc::new(RwLock::new(loudness_cache)));
+
+        let music_library = MusicLibrary::new(env::var("MUSIC_LIBRARY").ok().map(PathBuf::from));
+        let music_library = Arc::new(RwLock::new(music_library));
+        tokio::spawn(library::rebuild(music_library.clone()));
+        data.insert::<MusicLibrary>(music_library);
+    }
+
     #[cfg(feature = "translate")]
     {
         let secret: oauth2::ApplicationSecret = oauth2::read_application_secret(".google_auth.json").await.expect("There is no .google_auth.json");
@@ -65,6 +107,25 @@ async fn main() {
         data.insert::<GoogleProjectId>(Arc::new(google_project_id));
     }
 
+    // Docker stops containers with SIGTERM, write the queues down before the calls go away.
+    {
+        let data = client.data.clone();
+        let shard_manager = client.shard_manager.clone();
+        let mut sigterm = signal(SignalKind::terminate()).expect("Err registering SIGTERM handler");
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+
+            if let Err(why) = snapshot::save_all(&data).await {
+                println!("Err saving queue snapshots: {:?}", why);
+            }
+            shard_manager.lock().await.shutdown_all().await;
+        });
+    }
+
     if let Err(why) = client.start().await {
         println!("Client error: {:?}", why);
     }